@@ -0,0 +1,876 @@
+use crate::{
+    number::Percent,
+    pricing::{
+        Bandwidth, CashFlows, Dividends, ExpenseRatioSchedule, Financing, GlidePath, GlideSchedule,
+        GlideStep, Holding, Inflation, Leverage, LeverageGrid, LeverageMode, Liquidation,
+        MarginAccount, OutcomeTracking, PathSampling, Period, Portfolio, RateProcess, RateSeries,
+        RebalanceSchedule, Rebalancing, Reentry, Retirement, Rotation, ScheduleInterpolation,
+        Signal, StopRule, StopTrigger, TailExtension, TaxRates, TaxableAccount, Termination,
+        TradingCosts, VolatilityTarget, WithdrawalRule,
+    },
+};
+
+/// How the index's daily changes are drawn from the historical ones.
+#[derive(Debug, Clone, Copy)]
+pub enum Model {
+    /// Historical days, drawn with replacement.
+    Sampling,
+    /// A log-normal distribution with the historical mean and variance.
+    LogNormal,
+    /// A smoothed version of the historical distribution, optionally with fitted tails.
+    KernelDensity(Bandwidth, Option<TailExtension>),
+}
+
+/// Where the uniform draws every path is built from come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sequence {
+    PseudoRandom,
+    /// A scrambled Halton sequence with a dimension for every draw of the index, which
+    /// converges faster but makes the reported standard errors overstate the real ones.
+    Halton,
+}
+
+/// Everything a run is set up with. `Config::default()` is a quick look at daily reset and
+/// rebalanced leverage, and each scenario below builds on it to look at one feature.
+#[derive(Debug, Clone)]
+pub struct Config {
+    simulations: u64,
+    period: Period,
+    path_sampling: PathSampling,
+    sequence: Sequence,
+    model: Model,
+    // Daily changes of other assets portfolios can hold, numbered from 1 after the index
+    companion_files: Vec<String>,
+    dividends: Option<Dividends>,
+    financing: Financing,
+    cash_rates: Option<RateSeries>,
+    inflation: Option<Inflation>,
+    leverages: LeverageGrid,
+    expense_ratios: ExpenseRatioSchedule,
+    leverage_modes: Vec<LeverageMode>,
+    trading_costs: TradingCosts,
+    tracking: OutcomeTracking,
+}
+
+/// A named configuration that can be picked on the command line.
+pub struct Scenario {
+    name: &'static str,
+    description: &'static str,
+    config: fn() -> Config,
+}
+
+impl Scenario {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn description(&self) -> &'static str {
+        self.description
+    }
+
+    pub fn config(&self) -> Config {
+        (self.config)()
+    }
+}
+
+pub const SCENARIOS: &[Scenario] = &[
+    Scenario {
+        name: "default",
+        description: "Daily reset and monthly rebalanced leverage across the default grid",
+        config: Config::default,
+    },
+    Scenario {
+        name: "modes",
+        description: "Every way of holding leverage side by side, with trading costs",
+        config: Config::modes,
+    },
+    Scenario {
+        name: "portfolio",
+        description: "55% 3x index and 45% 3x companion, needs --companion FILE",
+        config: Config::portfolio,
+    },
+    Scenario {
+        name: "rates",
+        description: "Simulated financing rates, with cash earning the same rate",
+        config: Config::rates,
+    },
+    Scenario {
+        name: "log-normal",
+        description: "Log-normal days with the historical mean and variance",
+        config: Config::log_normal,
+    },
+    Scenario {
+        name: "fat-tails",
+        description: "Smoothed historical days with fitted Pareto tails",
+        config: Config::fat_tails,
+    },
+    Scenario {
+        name: "dividends",
+        description: "A dividend yield on top of the daily changes, with price return",
+        config: Config::dividend_yield,
+    },
+    Scenario {
+        name: "termination",
+        description: "Funds shut down after an 80% drawdown",
+        config: Config::termination,
+    },
+    Scenario {
+        name: "contributions",
+        description: "Monthly contributions and their money-weighted return",
+        config: Config::contributions,
+    },
+    Scenario {
+        name: "retirement",
+        description: "30 years of 4% inflation-adjusted withdrawals",
+        config: Config::retirement,
+    },
+    Scenario {
+        name: "taxes",
+        description: "Each variant also held in a taxable account, on a dividend-paying index",
+        config: Config::taxes,
+    },
+    Scenario {
+        name: "stops",
+        description: "Trailing and fixed stops laid over every variant, next to holding throughout",
+        config: Config::stops,
+    },
+    Scenario {
+        name: "lifecycle",
+        description: "Glide paths from more leverage early on to less later",
+        config: Config::lifecycle,
+    },
+];
+
+const DEFAULT_SIMULATIONS: u64 = 10_000;
+
+impl Config {
+    /// Parses `[scenario] [--simulations N] [--years N] [--independent] [--halton]
+    /// [--bandwidth X] [--rates FILE] [--expense-ratio PERCENT] [--leverage-step X]
+    /// [--withdrawal RULE] [--commission PERCENT] [--slippage X] [--companion FILE]...`,
+    /// starting from the default scenario.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut args = args.into_iter();
+        let mut config = None;
+        while let Some(arg) = args.next() {
+            let mut value =
+                |flag: &str| args.next().ok_or_else(|| format!("{} needs a value", flag));
+            config = Some(match arg.as_str() {
+                "--simulations" => {
+                    let simulations = value(&arg)?
+                        .parse()
+                        .map_err(|_| String::from("--simulations needs a whole number"))?;
+                    Self::parse_default(config).with_simulations(simulations)
+                }
+                "--years" => {
+                    let years = value(&arg)?
+                        .parse()
+                        .map_err(|_| String::from("--years needs a whole number"))?;
+                    Self::parse_default(config).with_period(Period::Years(years))
+                }
+                "--independent" => {
+                    Self::parse_default(config).with_path_sampling(PathSampling::Independent)
+                }
+                "--halton" => Self::parse_default(config).with_sequence(Sequence::Halton),
+                "--bandwidth" => {
+                    let bandwidth = value(&arg)?
+                        .parse()
+                        .ok()
+                        .filter(|bandwidth: &f64| bandwidth.is_finite() && *bandwidth > 0.0)
+                        .ok_or_else(|| String::from("--bandwidth needs a positive number"))?;
+                    let config = Self::parse_default(config);
+                    match config.model() {
+                        Model::KernelDensity(_, tail_extension) => config.with_model(
+                            Model::KernelDensity(Bandwidth::Fixed(bandwidth), tail_extension),
+                        ),
+                        _ => return Err(String::from("--bandwidth needs a smoothed model")),
+                    }
+                }
+                "--rates" => {
+                    let file = value(&arg)?;
+                    let rates = RateSeries::from_file(&file)
+                        .ok_or_else(|| format!("Couldn't read annual rates from {}", file))?;
+                    Self::parse_default(config).with_rates(rates)
+                }
+                "--expense-ratio" => {
+                    let annual_amount = value(&arg)?
+                        .parse()
+                        .ok()
+                        .filter(|percent: &f64| percent.is_finite() && *percent >= 0.0)
+                        .ok_or_else(|| String::from("--expense-ratio needs a percentage"))?;
+                    Self::parse_default(config).with_expense_ratios(ExpenseRatioSchedule::flat(
+                        Percent::from_percent(annual_amount),
+                    ))
+                }
+                "--leverage-step" => {
+                    let config = Self::parse_default(config);
+                    let highest = config.leverages().leverages().last().map(Leverage::amount);
+                    let step = value(&arg)?
+                        .parse()
+                        .ok()
+                        .filter(|step: &f64| {
+                            step.is_finite()
+                                && *step > 0.0
+                                && highest.is_some_and(|highest| *step <= highest)
+                        })
+                        .ok_or_else(|| {
+                            String::from("--leverage-step needs a step up to the highest leverage")
+                        })?;
+                    config.with_leverage_step(step)
+                }
+                "--withdrawal" => {
+                    let rule = match value(&arg)?.as_str() {
+                        "fixed" => WithdrawalRule::Fixed {
+                            rate: Percent::from_percent(4.0),
+                        },
+                        "percent" => WithdrawalRule::PercentOfPortfolio {
+                            rate: Percent::from_percent(4.0),
+                        },
+                        "guardrails" => WithdrawalRule::Guardrails {
+                            rate: Percent::from_percent(5.0),
+                            band: Percent::from_percent(20.0),
+                            adjustment: Percent::from_percent(10.0),
+                        },
+                        _ => {
+                            return Err(String::from(
+                                "--withdrawal needs fixed, percent or guardrails",
+                            ))
+                        }
+                    };
+                    let config = Self::parse_default(config);
+                    if config.tracking().retirement().is_none() {
+                        return Err(String::from("--withdrawal needs a retirement scenario"));
+                    }
+                    config.with_withdrawal_rule(rule)
+                }
+                "--commission" => {
+                    let cost = value(&arg)?
+                        .parse()
+                        .ok()
+                        .filter(|percent: &f64| percent.is_finite() && *percent >= 0.0)
+                        .ok_or_else(|| String::from("--commission needs a percentage"))?;
+                    Self::parse_default(config).with_trading_costs(TradingCosts::PerTrade {
+                        cost: Percent::from_percent(cost),
+                    })
+                }
+                "--slippage" => {
+                    let multiple = value(&arg)?
+                        .parse()
+                        .ok()
+                        .filter(|multiple: &f64| multiple.is_finite() && *multiple >= 0.0)
+                        .ok_or_else(|| String::from("--slippage needs a non-negative number"))?;
+                    Self::parse_default(config).with_trading_costs(TradingCosts::VolatilityScaled {
+                        multiple,
+                        lookback: Period::Days(21),
+                    })
+                }
+                "--companion" => {
+                    let file = value(&arg)?;
+                    Self::parse_default(config).with_companion_file(&file)
+                }
+                name if config.is_none() => SCENARIOS
+                    .iter()
+                    .find(|scenario| scenario.name() == name)
+                    .ok_or_else(|| format!("Unknown scenario or option: {}", name))?
+                    .config(),
+                other => return Err(format!("Unexpected argument: {}", other)),
+            });
+        }
+        Self::parse_default(config).validated()
+    }
+
+    /// Companions are paired with the index through the historical days it samples, so they
+    /// need the sampling model, and every portfolio holding needs its asset simulated.
+    fn validated(self) -> Result<Self, String> {
+        if !self.companion_files.is_empty() && !matches!(self.model, Model::Sampling) {
+            return Err(String::from("--companion needs the sampling model"));
+        }
+
+        let assets = 1 + self.companion_files.len();
+        let missing = self
+            .leverage_modes
+            .iter()
+            .filter_map(|mode| match mode {
+                LeverageMode::Portfolio(portfolio) => Some(portfolio),
+                _ => None,
+            })
+            .flat_map(Portfolio::holdings)
+            .find(|holding| holding.asset() >= assets);
+        match missing {
+            Some(holding) => Err(format!(
+                "A portfolio holds asset {} but there's no companion for it, add --companion FILE",
+                holding.asset()
+            )),
+            None => Ok(self),
+        }
+    }
+
+    fn parse_default(config: Option<Self>) -> Self {
+        config.unwrap_or_default()
+    }
+
+    /// Every way of holding leverage side by side, with cash earning interest, trades
+    /// costing 5bp of what's traded, and funds charging what typical 1x, 2x and 3x ETFs do.
+    pub fn modes() -> Self {
+        Self::default()
+            .with_leverages(LeverageGrid::new(&[
+                Leverage::new(1.0),
+                Leverage::new(2.0),
+                Leverage::new(3.0),
+            ]))
+            .with_expense_ratios(ExpenseRatioSchedule::new(
+                &[
+                    (Leverage::new(1.0), Percent::from_percent(0.03)),
+                    (Leverage::new(2.0), Percent::from_percent(0.95)),
+                    (Leverage::new(3.0), Percent::from_percent(0.91)),
+                ],
+                ScheduleInterpolation::Linear,
+            ))
+            .with_cash_rates(RateSeries::Constant(Percent::from_percent(2.0)))
+            .with_leverage_modes(&[
+                LeverageMode::DailyReset,
+                LeverageMode::Rebalanced(Rebalancing::new(
+                    RebalanceSchedule::quarterly(),
+                    Percent::from_percent(1.0),
+                )),
+                LeverageMode::Rebalanced(Rebalancing::new(
+                    RebalanceSchedule::Band {
+                        tolerance: Percent::from_percent(10.0),
+                    },
+                    Percent::from_percent(1.0),
+                )),
+                LeverageMode::VolatilityTarget(VolatilityTarget::new(
+                    Percent::from_percent(16.0),
+                    Period::Days(21),
+                    Leverage::new(0.0),
+                    Leverage::new(4.0),
+                    Percent::from_percent(1.0),
+                )),
+                LeverageMode::Rotation(Rotation::new(
+                    Signal::MovingAverage { days: 200 },
+                    Leverage::new(1.0),
+                    Period::Days(1),
+                    Percent::from_percent(0.1),
+                )),
+                LeverageMode::Rotation(Rotation::new(
+                    Signal::Drawdown {
+                        threshold: Percent::from_percent(20.0),
+                    },
+                    Leverage::new(0.0),
+                    Period::Days(1),
+                    Percent::from_percent(0.1),
+                )),
+                LeverageMode::Rotation(Rotation::new(
+                    Signal::Volatility {
+                        lookback: Period::Days(21),
+                        threshold: Percent::from_percent(20.0),
+                    },
+                    Leverage::new(1.0),
+                    Period::Days(1),
+                    Percent::from_percent(0.1),
+                )),
+                LeverageMode::Margin(MarginAccount::new(
+                    Percent::from_percent(1.0),
+                    Percent::from_percent(25.0),
+                    Liquidation::Partial {
+                        target_margin: Percent::from_percent(35.0),
+                    },
+                )),
+                LeverageMode::Margin(MarginAccount::new(
+                    Percent::from_percent(1.0),
+                    Percent::from_percent(25.0),
+                    Liquidation::Full,
+                )),
+            ])
+            .with_trading_costs(TradingCosts::Notional {
+                rate: Percent::from_percent(0.05),
+            })
+    }
+
+    /// 55% in 3x funds on the index and 45% in 3x funds on the first companion, e.g. UPRO and
+    /// TMF, rebalanced quarterly next to the index alone.
+    pub fn portfolio() -> Self {
+        Self::modes()
+            .with_leverages(LeverageGrid::new(&[Leverage::new(1.0)]))
+            .with_leverage_modes(&[
+                LeverageMode::DailyReset,
+                LeverageMode::Portfolio(Portfolio::new(
+                    &[
+                        Holding::new(0, Leverage::new(3.0), Percent::from_percent(55.0)),
+                        Holding::new(1, Leverage::new(3.0), Percent::from_percent(45.0)),
+                    ],
+                    RebalanceSchedule::quarterly(),
+                )),
+            ])
+    }
+
+    /// Financing rates that wander around 3% instead of staying at 2%, with cash earning
+    /// the same rates.
+    pub fn rates() -> Self {
+        let rates = RateSeries::Simulated(RateProcess::new(
+            Percent::from_percent(2.0),
+            Percent::from_percent(3.0),
+            0.2,
+            Percent::from_percent(1.0),
+            rand::random(),
+        ));
+        Self::default()
+            .with_financing(Financing::new(rates.clone(), Percent::from_percent(0.5)))
+            .with_cash_rates(rates)
+    }
+
+    pub fn log_normal() -> Self {
+        Self::default().with_model(Model::LogNormal)
+    }
+
+    pub fn fat_tails() -> Self {
+        Self::default().with_model(Model::KernelDensity(
+            Bandwidth::Silverman,
+            Some(TailExtension::new(Percent::from_percent(2.5))),
+        ))
+    }
+
+    /// Takes the daily changes as price-only and adds a 1.8% yield on top.
+    pub fn dividend_yield() -> Self {
+        Self::default().with_dividends(Dividends::Yield(Percent::from_percent(1.8)))
+    }
+
+    /// Funds closed once they're 80% below their peak, or after losing 40% in a day.
+    pub fn termination() -> Self {
+        Self::default().with_tracking(OutcomeTracking::new(
+            vec![
+                Termination::Drawdown(Percent::from_percent(80.0)),
+                Termination::DailyLoss(Percent::from_percent(40.0)),
+            ],
+            None,
+            None,
+            None,
+            Vec::new(),
+        ))
+    }
+
+    /// 1,000 added every month to a starting 100,000, in today's money.
+    pub fn contributions() -> Self {
+        Self::default()
+            .with_inflation(Inflation::Rates(RateSeries::Constant(
+                Percent::from_percent(2.5),
+            )))
+            .with_tracking(OutcomeTracking::new(
+                Vec::new(),
+                Some(CashFlows::new(100_000.0, 1_000.0, Period::Days(21))),
+                None,
+                None,
+                Vec::new(),
+            ))
+    }
+
+    pub fn retirement() -> Self {
+        Self::default()
+            .with_period(Period::Years(30))
+            .with_cash_rates(RateSeries::Constant(Percent::from_percent(2.0)))
+            .with_inflation(Inflation::Rates(RateSeries::Constant(
+                Percent::from_percent(2.5),
+            )))
+            .with_tracking(OutcomeTracking::new(
+                Vec::new(),
+                None,
+                Some(Retirement::new(
+                    1_000_000.0,
+                    WithdrawalRule::Fixed {
+                        rate: Percent::from_percent(4.0),
+                    },
+                )),
+                None,
+                Vec::new(),
+            ))
+    }
+
+    /// Dividends are paid out so they're taxed as they come in.
+    pub fn taxes() -> Self {
+        Self::dividend_yield().with_tracking(OutcomeTracking::new(
+            Vec::new(),
+            None,
+            None,
+            Some(TaxableAccount::new(TaxRates::new(
+                Percent::from_percent(37.0),
+                Percent::from_percent(20.0),
+                Percent::from_percent(20.0),
+            ))),
+            Vec::new(),
+        ))
+    }
+
+    /// Sold after a 10% or 20% fall from the peak and bought back once as far off the low,
+    /// or after a 15% loss and bought back a quarter later.
+    pub fn stops() -> Self {
+        let trailing = |percent| {
+            StopRule::new(
+                StopTrigger::Trailing(Percent::from_percent(percent)),
+                Reentry::Recovery(Percent::from_percent(percent)),
+                Period::Days(21),
+            )
+        };
+        Self::default()
+            .with_cash_rates(RateSeries::Constant(Percent::from_percent(2.0)))
+            .with_tracking(OutcomeTracking::new(
+                Vec::new(),
+                None,
+                None,
+                None,
+                vec![
+                    trailing(10.0),
+                    trailing(20.0),
+                    StopRule::new(
+                        StopTrigger::Loss(Percent::from_percent(15.0)),
+                        Reentry::After(Period::Days(63)),
+                        Period::Days(21),
+                    ),
+                ],
+            ))
+    }
+
+    /// Lifecycle investing over 20 years: a straight glide, steps, and an age-based rule.
+    pub fn lifecycle() -> Self {
+        let glide_path = |schedule| {
+            LeverageMode::GlidePath(GlidePath::new(
+                schedule,
+                RebalanceSchedule::monthly(),
+                Percent::from_percent(1.0),
+            ))
+        };
+        Self::default()
+            .with_period(Period::Years(20))
+            .with_leverages(LeverageGrid::new(&[Leverage::new(1.0)]))
+            .with_cash_rates(RateSeries::Constant(Percent::from_percent(2.0)))
+            .with_leverage_modes(&[
+                glide_path(GlideSchedule::Linear {
+                    start: Leverage::new(2.0),
+                    end: Leverage::new(1.0),
+                }),
+                glide_path(GlideSchedule::steps(&[
+                    GlideStep::new(0, Leverage::new(2.0)),
+                    GlideStep::new(10, Leverage::new(1.5)),
+                    GlideStep::new(15, Leverage::new(1.0)),
+                ])),
+                glide_path(GlideSchedule::AgeBased {
+                    starting_age: 30,
+                    base: 120,
+                    min_leverage: Leverage::new(0.3),
+                    max_leverage: Leverage::new(1.0),
+                }),
+            ])
+    }
+
+    pub fn with_simulations(self, simulations: u64) -> Self {
+        Self {
+            simulations,
+            ..self
+        }
+    }
+
+    pub fn with_period(self, period: Period) -> Self {
+        Self { period, ..self }
+    }
+
+    pub fn with_path_sampling(self, path_sampling: PathSampling) -> Self {
+        Self {
+            path_sampling,
+            ..self
+        }
+    }
+
+    pub fn with_sequence(self, sequence: Sequence) -> Self {
+        Self { sequence, ..self }
+    }
+
+    pub fn with_model(self, model: Model) -> Self {
+        Self { model, ..self }
+    }
+
+    pub fn with_companion_file(mut self, file: &str) -> Self {
+        self.companion_files.push(String::from(file));
+        self
+    }
+
+    pub fn with_dividends(self, dividends: Dividends) -> Self {
+        Self {
+            dividends: Some(dividends),
+            ..self
+        }
+    }
+
+    pub fn with_financing(self, financing: Financing) -> Self {
+        Self { financing, ..self }
+    }
+
+    pub fn with_cash_rates(self, cash_rates: RateSeries) -> Self {
+        Self {
+            cash_rates: Some(cash_rates),
+            ..self
+        }
+    }
+
+    /// Finances leverage at the given rates, keeping the swap spread, and has cash earn
+    /// them when it earns anything.
+    pub fn with_rates(self, rates: RateSeries) -> Self {
+        Self {
+            financing: Financing::new(rates.clone(), self.financing.swap_spread()),
+            cash_rates: self.cash_rates.as_ref().map(|_| rates),
+            ..self
+        }
+    }
+
+    pub fn with_inflation(self, inflation: Inflation) -> Self {
+        Self {
+            inflation: Some(inflation),
+            ..self
+        }
+    }
+
+    pub fn with_leverages(self, leverages: LeverageGrid) -> Self {
+        Self { leverages, ..self }
+    }
+
+    /// Adds every `step` of leverage up to the highest already in the grid.
+    pub fn with_leverage_step(self, step: f64) -> Self {
+        let highest = self
+            .leverages
+            .leverages()
+            .last()
+            .map_or(step, Leverage::amount);
+        let leverages = self
+            .leverages
+            .merge(&LeverageGrid::range(step, highest, step));
+        self.with_leverages(leverages)
+    }
+
+    pub fn with_expense_ratios(self, expense_ratios: ExpenseRatioSchedule) -> Self {
+        Self {
+            expense_ratios,
+            ..self
+        }
+    }
+
+    pub fn with_leverage_modes(self, leverage_modes: &[LeverageMode]) -> Self {
+        Self {
+            leverage_modes: Vec::from(leverage_modes),
+            ..self
+        }
+    }
+
+    pub fn with_trading_costs(self, trading_costs: TradingCosts) -> Self {
+        Self {
+            trading_costs,
+            ..self
+        }
+    }
+
+    pub fn with_tracking(self, tracking: OutcomeTracking) -> Self {
+        Self { tracking, ..self }
+    }
+
+    /// Withdraws by `rule` from the same starting balance, if retirement is simulated.
+    pub fn with_withdrawal_rule(self, rule: WithdrawalRule) -> Self {
+        match self.tracking.retirement() {
+            Some(retirement) => {
+                let retirement = Retirement::new(retirement.starting_balance(), rule);
+                let tracking = self.tracking.clone().with_retirement(retirement);
+                self.with_tracking(tracking)
+            }
+            None => self,
+        }
+    }
+
+    pub fn simulations(&self) -> u64 {
+        self.simulations
+    }
+
+    pub fn period(&self) -> Period {
+        self.period
+    }
+
+    pub fn path_sampling(&self) -> PathSampling {
+        self.path_sampling
+    }
+
+    pub fn sequence(&self) -> Sequence {
+        self.sequence
+    }
+
+    pub fn model(&self) -> Model {
+        self.model
+    }
+
+    pub fn companion_files(&self) -> &[String] {
+        &self.companion_files
+    }
+
+    pub fn dividends(&self) -> Option<&Dividends> {
+        self.dividends.as_ref()
+    }
+
+    pub fn financing(&self) -> &Financing {
+        &self.financing
+    }
+
+    pub fn cash_rates(&self) -> Option<&RateSeries> {
+        self.cash_rates.as_ref()
+    }
+
+    pub fn inflation(&self) -> Option<&Inflation> {
+        self.inflation.as_ref()
+    }
+
+    pub fn leverages(&self) -> &LeverageGrid {
+        &self.leverages
+    }
+
+    pub fn expense_ratios(&self) -> &ExpenseRatioSchedule {
+        &self.expense_ratios
+    }
+
+    pub fn leverage_modes(&self) -> &[LeverageMode] {
+        &self.leverage_modes
+    }
+
+    pub fn trading_costs(&self) -> &TradingCosts {
+        &self.trading_costs
+    }
+
+    pub fn tracking(&self) -> &OutcomeTracking {
+        &self.tracking
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            simulations: DEFAULT_SIMULATIONS,
+            period: Period::Years(5),
+            path_sampling: PathSampling::Antithetic,
+            sequence: Sequence::PseudoRandom,
+            model: Model::Sampling,
+            companion_files: Vec::new(),
+            dividends: None,
+            financing: Financing::new(
+                RateSeries::Constant(Percent::from_percent(2.0)),
+                Percent::from_percent(0.5),
+            ),
+            cash_rates: None,
+            inflation: None,
+            leverages: LeverageGrid::default(),
+            expense_ratios: ExpenseRatioSchedule::default(),
+            leverage_modes: vec![
+                LeverageMode::DailyReset,
+                LeverageMode::Rebalanced(Rebalancing::new(
+                    RebalanceSchedule::monthly(),
+                    Percent::from_percent(1.0),
+                )),
+            ],
+            trading_costs: TradingCosts::None,
+            tracking: OutcomeTracking::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|&arg| String::from(arg)).collect()
+    }
+
+    #[test]
+    fn test_from_args() {
+        let config = Config::from_args(args(&[])).unwrap();
+        assert_eq!(config.simulations(), DEFAULT_SIMULATIONS);
+        assert_eq!(config.leverage_modes().len(), 2);
+
+        let config = Config::from_args(args(&[
+            "retirement",
+            "--simulations",
+            "200",
+            "--years",
+            "40",
+        ]))
+        .unwrap();
+        assert_eq!(config.simulations(), 200);
+        assert_eq!(config.period(), Period::Years(40));
+        assert!(config.tracking().retirement().is_some());
+
+        let config = Config::from_args(args(&["--independent", "--halton"])).unwrap();
+        assert_eq!(config.path_sampling(), PathSampling::Independent);
+        assert_eq!(config.sequence(), Sequence::Halton);
+
+        let config = Config::from_args(args(&["fat-tails", "--bandwidth", "0.002"])).unwrap();
+        assert!(matches!(
+            config.model(),
+            Model::KernelDensity(Bandwidth::Fixed(bandwidth), Some(_)) if bandwidth == 0.002
+        ));
+        assert!(Config::from_args(args(&["--bandwidth", "0.002"])).is_err());
+        assert!(Config::from_args(args(&["fat-tails", "--bandwidth", "-1"])).is_err());
+
+        let file = std::env::temp_dir().join("stock-sim-rates.csv");
+        std::fs::write(&file, "0.05\n0.06\n").unwrap();
+        let config =
+            Config::from_args(args(&["rates", "--rates", file.to_str().unwrap()])).unwrap();
+        let expected = RateSeries::Historical(vec![
+            Percent::from_decimal(0.05),
+            Percent::from_decimal(0.06),
+        ])
+        .daily_rates(0, Period::Days(3));
+        let decimals =
+            |rates: Vec<Percent>| rates.iter().map(Percent::as_decimal).collect::<Vec<_>>();
+        assert_eq!(
+            decimals(config.financing().daily_base_rates(0, Period::Days(3))),
+            decimals(expected.clone())
+        );
+        assert_eq!(
+            decimals(config.cash_rates().unwrap().daily_rates(0, Period::Days(3))),
+            decimals(expected)
+        );
+        assert!(Config::from_args(args(&["--rates", "missing.csv"])).is_err());
+
+        let config = Config::from_args(args(&["modes", "--expense-ratio", "0.5"])).unwrap();
+        [Leverage::new(1.0), Leverage::new(3.0)]
+            .iter()
+            .for_each(|&leverage| {
+                let annual_amount = config.expense_ratios().annual_amount(leverage);
+                assert!((annual_amount.as_decimal() - 0.005).abs() < 1e-12);
+            });
+
+        let config = Config::from_args(args(&["modes", "--leverage-step", "0.5"])).unwrap();
+        assert_eq!(config.leverages(), &LeverageGrid::range(0.5, 3.0, 0.5));
+        assert!(Config::from_args(args(&["modes", "--leverage-step", "4"])).is_err());
+
+        let config =
+            Config::from_args(args(&["retirement", "--withdrawal", "guardrails"])).unwrap();
+        assert!(matches!(
+            config
+                .tracking()
+                .retirement()
+                .map(|retirement| retirement.rule()),
+            Some(WithdrawalRule::Guardrails { .. })
+        ));
+        assert!(Config::from_args(args(&["--withdrawal", "percent"])).is_err());
+        assert!(Config::from_args(args(&["retirement", "--withdrawal", "all"])).is_err());
+
+        let config = Config::from_args(args(&["modes", "--slippage", "0.1"])).unwrap();
+        assert_eq!(
+            config.trading_costs(),
+            &TradingCosts::VolatilityScaled {
+                multiple: 0.1,
+                lookback: Period::Days(21),
+            }
+        );
+        assert!(Config::from_args(args(&["modes", "--commission", "-1"])).is_err());
+
+        assert!(Config::from_args(args(&["portfolio"])).is_err());
+        assert!(Config::from_args(args(&["portfolio", "--companion", "bonds.csv"])).is_ok());
+        assert!(Config::from_args(args(&["log-normal", "--companion", "bonds.csv"])).is_err());
+
+        assert!(Config::from_args(args(&["nonsense"])).is_err());
+        assert!(Config::from_args(args(&["--simulations"])).is_err());
+        assert!(Config::from_args(args(&["--simulations", "100", "taxes"])).is_err());
+    }
+}
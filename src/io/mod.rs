@@ -2,4 +2,5 @@ mod reader;
 mod writer;
 
 pub use reader::*;
+#[allow(unused_imports)]
 pub use writer::*;
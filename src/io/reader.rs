@@ -11,6 +11,6 @@ where
     P: AsRef<Path>,
 {
     let file = File::open(path).ok()?;
-    let lines = BufReader::new(file).lines().filter_map(Result::ok);
+    let lines = BufReader::new(file).lines().map_while(Result::ok);
     Some(lines)
 }
@@ -10,7 +10,7 @@ pub struct Writer {
 impl Writer {
     pub fn new(file_path: &str, columns: u16) -> Self {
         let csv_writer = csv::Writer::from_path(file_path)
-            .expect(&format!("Failed to open writer to file: {}", file_path));
+            .unwrap_or_else(|_| panic!("Failed to open writer to file: {}", file_path));
 
         Self {
             columns,
@@ -29,9 +29,9 @@ impl Writer {
 
         let columns = columns as u16;
         let mut csv_writer = csv::Writer::from_path(file_path)
-            .expect(&format!("Failed to open writer to file: {}", file_path));
+            .unwrap_or_else(|_| panic!("Failed to open writer to file: {}", file_path));
 
-        if !csv_writer.write_record(header).is_ok() {
+        if csv_writer.write_record(header).is_err() {
             panic!("Failed to write header row.");
         }
 
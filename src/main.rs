@@ -1,26 +1,41 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    error::Error,
+};
 
+use config::{Config, Model, Sequence, SCENARIOS};
 use io::read_lines;
 use number::Percent;
 use pricing::{
-    AlternatingPricingStrategy, Period, PriceChange, PriceHistory, PriceHistoryDescriptor,
-    PriceHistoryVariants, PricingStrategy, SamplingPricingStrategy,
+    CashFlows, KernelDensityPricingStrategy, Leverage, LeverageMode, LogNormalPricingStrategy,
+    MarketHistory, PairedSamplingPricingStrategy, PathSampling, Period, PriceChange, PriceHistory,
+    PriceHistoryDescriptor, PriceHistoryVariants, PricingStrategy, Retirement,
+    SamplingPricingStrategy, StopRule, TaxableAccount,
 };
+use random::{GeneralizedPareto, HaltonSequence, PseudoRandomSequence, UniformSequence};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use stats::{
-    calculate_statistic, AveragePriceChange, ComputedStatistic, MatchingPriceChangeRatio,
-    MatchingPriceChangeRatioContext, MedianPriceChange, StandardDeviationPriceChange,
-    StandardDeviationPriceChangeContext,
+    analytic_kelly_leverage, calculate_grouped_statistic, calculate_statistic,
+    growth_optimal_leverage, log_wealth, AveragePriceChange, CashFlowDistribution,
+    ComputedStatistic, ControlVariateContext, ControlVariateEstimate, CostDragDistribution,
+    LeverageBound, LogWealth, MatchingPriceChangeRatio, MatchingPriceChangeRatioContext,
+    MedianPriceChange, OutcomeTarget, PriceReturn, Real, RealizedLeverageDistribution,
+    RetirementDistribution, RuinProbability, StandardDeviationPriceChange,
+    StandardDeviationPriceChangeContext, StandardErrorPriceChange, StopDistribution,
+    TaxDistribution, UntrackedOutcome,
 };
 
+mod config;
 mod io;
 mod number;
 mod pricing;
+mod random;
 mod stats;
 mod types;
 
-fn load_daily_price_changes() -> Vec<PriceChange> {
-    let lines = read_lines("resources/daily-changes.csv").expect("Failed to read file");
+fn load_daily_price_changes(path: &str) -> Vec<PriceChange> {
+    let lines = read_lines(path).expect("Failed to read file");
     lines
         .map(|line| line.replace(|c: char| !c.is_ascii(), ""))
         .map(|line| {
@@ -39,33 +54,297 @@ fn load_daily_price_changes() -> Vec<PriceChange> {
         .collect()
 }
 
-const SIMULATIONS: u64 = 10_000;
+fn print_usage() {
+    println!("Usage: stock-sim [SCENARIO] [--simulations N] [--years N] [--independent]");
+    println!("                 [--halton] [--bandwidth X] [--rates FILE] [--leverage-step X]");
+    println!("                 [--expense-ratio PERCENT] [--withdrawal fixed|percent|guardrails]");
+    println!("                 [--commission PERCENT] [--slippage X] [--companion FILE]...");
+    println!();
+    println!("Scenarios:");
+    SCENARIOS
+        .iter()
+        .for_each(|scenario| println!("  {: <14} {}", scenario.name(), scenario.description()));
+}
 
-fn main() {
-    println!("Running with {} simulations", SIMULATIONS);
-    let price_change_options: Vec<PriceChange> = load_daily_price_changes();
-    // let price_change_options = vec![
-    //     Percent::from_percent(-2.0).into(),
-    //     Percent::from_percent(2.05).into(),
-    // ];
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        print_usage();
+        return Ok(());
+    }
+    let config = Config::from_args(args)?;
 
-    let pricing_strategy = SamplingPricingStrategy::new(&price_change_options);
-    // let pricing_strategy = AlternatingPricingStrategy::new(&price_change_options);
+    let price_change_options: Vec<PriceChange> =
+        load_daily_price_changes("resources/daily-changes.csv");
 
-    let period = Period::Years(5);
-    let price_history_variants = (0..SIMULATIONS)
-        .into_par_iter()
-        .map(|_| pricing_strategy.calculate_price_history(0..period.as_days()))
-        // .inspect(|price_history| println!("{:+.3}", price_history))
-        .map(|price_history| PriceHistoryVariants::new(price_history, period))
-        .collect::<Vec<_>>();
+    match config.model() {
+        Model::Sampling => run(
+            &config,
+            &price_change_options,
+            SamplingPricingStrategy::new(&price_change_options),
+        )?,
+        Model::LogNormal => run(
+            &config,
+            &price_change_options,
+            LogNormalPricingStrategy::new(&price_change_options),
+        )?,
+        Model::KernelDensity(bandwidth, tail_extension) => {
+            let pricing_strategy =
+                KernelDensityPricingStrategy::new(&price_change_options, bandwidth, tail_extension);
+            print_kernel_density(&pricing_strategy);
+            run(&config, &price_change_options, pricing_strategy)?
+        }
+    }
+
+    Ok(())
+}
+
+/// The fitted smoothing, with each tail that got a Pareto fit.
+fn print_kernel_density(pricing_strategy: &KernelDensityPricingStrategy) {
+    let describe_tail = |tail: Option<GeneralizedPareto>| match tail {
+        Some(tail) => format!("shape {:.3}, scale {:.5}", tail.shape(), tail.scale()),
+        None => String::from("smoothed"),
+    };
+    println!(
+        "Kernel bandwidth: {:.5} | Lower tail: {} | Upper tail: {}",
+        pricing_strategy.bandwidth(),
+        describe_tail(pricing_strategy.lower_tail()),
+        describe_tail(pricing_strategy.upper_tail()),
+    );
+}
+
+fn run<P: PricingStrategy>(
+    config: &Config,
+    price_change_options: &[PriceChange],
+    pricing_strategy: P,
+) -> Result<(), UntrackedOutcome> {
+    let seed = rand::random();
+    println!(
+        "Running with {} simulations | Model: {:?} | Sampling: {:?} | Sequence: {:?} | Seed: {}",
+        config.simulations(),
+        config.model(),
+        config.path_sampling(),
+        config.sequence(),
+        seed
+    );
 
+    let price_history_variants = match config.sequence() {
+        Sequence::PseudoRandom => simulate(
+            config,
+            price_change_options,
+            &pricing_strategy,
+            &PseudoRandomSequence::new(seed),
+        ),
+        Sequence::Halton => {
+            let dimensions =
+                config.period().as_days() * pricing_strategy.uniforms_per_price_change();
+            simulate(
+                config,
+                price_change_options,
+                &pricing_strategy,
+                &HaltonSequence::new(seed, dimensions as usize),
+            )
+        }
+    };
+    let period = config.period();
+    let path_sampling = config.path_sampling();
     let descriptors = Vec::from(price_history_variants[0].descriptors());
+    let stats = calculate_stat_groups(&price_history_variants, config)?;
 
+    if config.dividends().is_some() {
+        let annualized_mean = |total: fn(&PriceHistoryVariants) -> PriceChange| {
+            let sum = price_history_variants
+                .iter()
+                .map(|variants| {
+                    total(variants)
+                        .annualized_return(period)
+                        .percent_change()
+                        .as_decimal()
+                })
+                .sum::<f64>();
+            Percent::from_decimal(sum / price_history_variants.len() as f64)
+        };
+        println!(
+            "Index: {:.2}/yr price return, {:.2}/yr total return",
+            annualized_mean(PriceHistoryVariants::underlying_price_change),
+            annualized_mean(PriceHistoryVariants::underlying_total_return)
+        );
+    }
+
+    print_returns(&stats);
+    print_distributions(&stats);
+    if stats
+        .iter()
+        .any(|stat_group| stat_group.ruin.average_ruin_day().is_some())
+    {
+        print_ruin(&stats);
+    }
+    if config
+        .leverage_modes()
+        .iter()
+        .any(|mode| *mode != LeverageMode::DailyReset)
+    {
+        print_trading(&stats);
+    }
+    if config.dividends().is_some() || config.inflation().is_some() {
+        print_price_and_real_returns(&stats, config);
+    }
+
+    let tracking = config.tracking();
+    if config.inflation().is_some()
+        && (tracking.cash_flows().is_some()
+            || tracking.retirement().is_some()
+            || tracking.taxes().is_some())
+    {
+        println!("Balances and withdrawals below are in today's money");
+    }
+
+    if let Some(cash_flows) = tracking.cash_flows() {
+        print_cash_flows(&price_history_variants, cash_flows)?;
+    }
+
+    if let Some(retirement) = tracking.retirement() {
+        print_retirement(&price_history_variants, retirement)?;
+    }
+
+    if let Some(taxes) = tracking.taxes() {
+        print_taxes(&price_history_variants, taxes)?;
+    }
+
+    if !tracking.stops().is_empty() {
+        print_stops(&price_history_variants)?;
+    }
+
+    let average_rate = |rates: Vec<Percent>| {
+        Percent::from_decimal(
+            rates.iter().map(|rate| rate.as_decimal()).sum::<f64>() / rates.len() as f64,
+        )
+    };
+    let average_cash_rate = config.cash_rates().map_or(Percent::zero(), |cash_rates| {
+        average_rate(cash_rates.daily_rates(0, period))
+    });
+    print_kelly_leverages(
+        &stats,
+        analytic_kelly_leverage(
+            price_change_options,
+            average_rate(config.financing().daily_borrowing_rates(0, period)),
+            average_cash_rate,
+        ),
+    );
+
+    print_target_leverages(&price_history_variants, &descriptors, period)?;
+
+    if let Some(expected_control) = pricing_strategy.expected_total_price_change(period) {
+        print_control_variate_estimates(
+            &price_history_variants,
+            path_sampling,
+            &descriptors,
+            expected_control,
+            period,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Simulates every path of the configured market and the variants held on each of them.
+fn simulate<P, S>(
+    config: &Config,
+    price_change_options: &[PriceChange],
+    pricing_strategy: &P,
+    uniform_sequence: &S,
+) -> Vec<PriceHistoryVariants>
+where
+    P: PricingStrategy,
+    S: UniformSequence,
+{
+    let period = config.period();
+    let path_sampling = config.path_sampling();
+    let financing = config.financing();
+
+    // Sampled on the same historical days as the index
+    let companion_strategies: Vec<PairedSamplingPricingStrategy> = config
+        .companion_files()
+        .iter()
+        .map(|file| {
+            PairedSamplingPricingStrategy::new(
+                price_change_options,
+                &load_daily_price_changes(file),
+            )
+        })
+        .collect();
+
+    let draws = config.simulations() / path_sampling.paths_per_draw();
+    (0..draws)
+        .into_par_iter()
+        .flat_map_iter(|draw| {
+            let borrowing_rates = financing.daily_borrowing_rates(draw, period);
+            let base_rates = financing.daily_base_rates(draw, period);
+            let daily_cash_rates = config
+                .cash_rates()
+                .map(|cash_rates| cash_rates.daily_rates(draw, period));
+            let companions = companion_strategies
+                .iter()
+                .map(|strategy| {
+                    path_sampling.price_histories(strategy, uniform_sequence, draw, period)
+                })
+                .collect::<Vec<_>>();
+            let dividend_histories = config
+                .dividends()
+                .map(|dividends| dividends.price_histories(path_sampling, period));
+            let inflations = config
+                .inflation()
+                .map(|inflation| inflation.price_histories(path_sampling, draw, period));
+            path_sampling
+                .price_histories(pricing_strategy, uniform_sequence, draw, period)
+                .into_iter()
+                .enumerate()
+                .map(move |(path, price_history)| {
+                    let mut assets = vec![price_history];
+                    assets.extend(companions.iter().map(|paths| paths[path].clone()));
+                    let market_history =
+                        MarketHistory::with_assets(assets, borrowing_rates.clone())
+                            .with_base_rates(base_rates.clone());
+                    let market_history = match &daily_cash_rates {
+                        Some(daily_cash_rates) => {
+                            market_history.with_cash_rates(daily_cash_rates.clone())
+                        }
+                        None => market_history,
+                    };
+                    let market_history = match &dividend_histories {
+                        Some(dividend_histories) => {
+                            market_history.with_dividends(dividend_histories[path].clone())
+                        }
+                        None => market_history,
+                    };
+                    match &inflations {
+                        Some(inflations) => market_history.with_inflation(inflations[path].clone()),
+                        None => market_history,
+                    }
+                })
+        })
+        .map(|market_history| {
+            PriceHistoryVariants::new(
+                &market_history,
+                period,
+                config.leverages(),
+                config.expense_ratios(),
+                config.leverage_modes(),
+                config.trading_costs(),
+                config.tracking(),
+            )
+        })
+        .collect()
+}
+
+fn calculate_stat_groups(
+    price_history_variants: &[PriceHistoryVariants],
+    config: &Config,
+) -> Result<Vec<StatGroup>, UntrackedOutcome> {
     let averages: HashMap<PriceHistoryDescriptor, AveragePriceChange> =
-        calculate_statistic(&price_history_variants, None)
+        calculate_statistic(price_history_variants, None)?
             .map(|value: ComputedStatistic<AveragePriceChange>| {
-                (value.descriptor(), value.statistic().clone())
+                (value.descriptor(), *value.statistic())
             })
             .collect();
 
@@ -76,69 +355,209 @@ fn main() {
             .collect();
 
     let stdevs: HashMap<PriceHistoryDescriptor, StandardDeviationPriceChange> =
-        calculate_statistic(&price_history_variants, Some(&stdev_context))
+        calculate_statistic(price_history_variants, Some(&stdev_context))?
             .map(|value: ComputedStatistic<StandardDeviationPriceChange>| {
-                (value.descriptor(), value.statistic().clone())
+                (value.descriptor(), *value.statistic())
+            })
+            .collect();
+
+    // Treating every path as independent gives the error plain Monte Carlo would have with
+    // the same number of paths; grouping by draw gives the error actually achieved.
+    let path_errors: HashMap<PriceHistoryDescriptor, StandardErrorPriceChange> =
+        calculate_statistic(price_history_variants, None)?
+            .map(|value: ComputedStatistic<StandardErrorPriceChange>| {
+                (value.descriptor(), *value.statistic())
+            })
+            .collect();
+
+    let draw_errors: HashMap<PriceHistoryDescriptor, StandardErrorPriceChange> =
+        calculate_grouped_statistic(price_history_variants, config.path_sampling(), None)?
+            .map(|value: ComputedStatistic<StandardErrorPriceChange>| {
+                (value.descriptor(), *value.statistic())
             })
             .collect();
 
     let medians: HashMap<PriceHistoryDescriptor, MedianPriceChange> =
-        calculate_statistic(&price_history_variants, None)
+        calculate_statistic(price_history_variants, None)?
             .map(|value: ComputedStatistic<MedianPriceChange>| {
                 (value.descriptor(), value.statistic().clone())
             })
             .collect();
 
-    let ratio_contexts: HashMap<PriceHistoryDescriptor, MatchingPriceChangeRatioContext> =
-        descriptors
-            .iter()
-            .map(|&descriptor| {
-                let context =
-                    MatchingPriceChangeRatioContext::new(move |price_change: PriceChange| {
-                        price_change.annualized_return(period).percent_change()
-                            >= Percent::from_percent(15.0).into()
-                    });
-                (descriptor, context)
+    let price_return_averages: HashMap<PriceHistoryDescriptor, PriceReturn<AveragePriceChange>> =
+        calculate_statistic(price_history_variants, None)?
+            .map(
+                |value: ComputedStatistic<PriceReturn<AveragePriceChange>>| {
+                    (value.descriptor(), *value.statistic())
+                },
+            )
+            .collect();
+
+    let real_averages: HashMap<PriceHistoryDescriptor, Real<AveragePriceChange>> =
+        calculate_statistic(price_history_variants, None)?
+            .map(|value: ComputedStatistic<Real<AveragePriceChange>>| {
+                (value.descriptor(), *value.statistic())
             })
             .collect();
 
-    let _loss_ratios = calculate_statistic::<MatchingPriceChangeRatio, _>(
-        &price_history_variants,
-        Some(&ratio_contexts),
-    )
-    .map(|value| (value.descriptor(), value.statistic().clone()))
-    .collect::<HashMap<_, _>>();
+    let real_medians: HashMap<PriceHistoryDescriptor, Real<MedianPriceChange>> =
+        calculate_statistic(price_history_variants, None)?
+            .map(|value: ComputedStatistic<Real<MedianPriceChange>>| {
+                (value.descriptor(), value.statistic().clone())
+            })
+            .collect();
 
-    let mut stats: Vec<StatGroup> = descriptors
-        .iter()
+    let ruins: HashMap<PriceHistoryDescriptor, RuinProbability> =
+        calculate_statistic(price_history_variants, None)?
+            .map(|value: ComputedStatistic<RuinProbability>| {
+                (value.descriptor(), *value.statistic())
+            })
+            .collect();
+
+    let cost_drags: HashMap<PriceHistoryDescriptor, CostDragDistribution> =
+        calculate_statistic(price_history_variants, None)?
+            .map(|value: ComputedStatistic<CostDragDistribution>| {
+                (value.descriptor(), value.statistic().clone())
+            })
+            .collect();
+
+    let realized_leverages: HashMap<PriceHistoryDescriptor, RealizedLeverageDistribution> =
+        calculate_statistic(price_history_variants, None)?
+            .map(|value: ComputedStatistic<RealizedLeverageDistribution>| {
+                (value.descriptor(), value.statistic().clone())
+            })
+            .collect();
+
+    let log_wealths: HashMap<PriceHistoryDescriptor, LogWealth> =
+        calculate_statistic(price_history_variants, None)?
+            .map(|value: ComputedStatistic<LogWealth>| (value.descriptor(), *value.statistic()))
+            .collect();
+
+    let mut stats: Vec<StatGroup> = averages
+        .keys()
         .map(|descriptor| {
             let percentiles = (0..10)
-                .into_iter()
                 .map(|i| i as f64 / 20.0)
                 .map(|percentile| medians[descriptor].percentile(Percent::from_decimal(percentile)))
-                // .map(|price_change| price_change.annualized_return(period))
                 .collect::<Vec<_>>();
 
             StatGroup {
                 descriptor: *descriptor,
-                average: averages[descriptor].average(),
+                average: draw_errors[descriptor].mean(),
                 annualized_average: averages[descriptor].annualized_average(),
                 stdev: stdevs[descriptor].stdev(),
                 median: medians[descriptor].median(),
+                price_return_annualized_average: price_return_averages[descriptor]
+                    .value()
+                    .annualized_average(),
+                real_annualized_average: real_averages[descriptor].value().annualized_average(),
+                real_median: real_medians[descriptor].value().median(),
                 min: medians[descriptor].min(),
                 max: medians[descriptor].max(),
                 inner_quartile_range: medians[descriptor].inner_quartile_range(),
+                standard_error: draw_errors[descriptor].standard_error(),
+                variance_reduction: draw_errors[descriptor]
+                    .variance_reduction(&path_errors[descriptor]),
+                ruin: ruins[descriptor],
+                realized_leverage: realized_leverages[descriptor].clone(),
+                cost_drag: cost_drags[descriptor].clone(),
+                log_wealth: log_wealths[descriptor],
                 percentiles,
             }
         })
         .collect();
     stats.sort();
 
+    Ok(stats)
+}
+
+/// The columns every per-variant table starts with.
+fn describe(descriptor: &PriceHistoryDescriptor) -> String {
+    format!(
+        "Years: {:.1} | Leverage: {: <4.1} | Mode: {: <11}",
+        descriptor.period().as_years(),
+        descriptor.leverage().amount(),
+        describe_variant_mode(descriptor),
+    )
+}
+
+/// Variants that only differ in leverage, compared across the grid.
+type GridKey = (
+    Period,
+    LeverageMode,
+    Option<StopRule>,
+    Option<TaxableAccount>,
+);
+
+fn grid_key(descriptor: &PriceHistoryDescriptor) -> GridKey {
+    (
+        descriptor.period(),
+        descriptor.mode(),
+        descriptor.stop(),
+        descriptor.taxes(),
+    )
+}
+
+/// The mode along with any stop laid over it, marked when the variant is held in a
+/// taxable account.
+fn describe_mode(
+    mode: LeverageMode,
+    stop: Option<StopRule>,
+    taxes: Option<TaxableAccount>,
+) -> String {
+    describe_overlays(mode.to_string(), stop, taxes)
+}
+
+/// The mode of a single variant. A volatility target shows the volatility the variant
+/// actually aims for, which scales with its leverage.
+fn describe_variant_mode(descriptor: &PriceHistoryDescriptor) -> String {
+    let mode = match descriptor.mode() {
+        LeverageMode::VolatilityTarget(target) => {
+            format!("Vol {:.0}", target.scaled_target(descriptor.leverage()))
+        }
+        mode => mode.to_string(),
+    };
+    describe_overlays(mode, descriptor.stop(), descriptor.taxes())
+}
+
+fn describe_overlays(
+    mode: String,
+    stop: Option<StopRule>,
+    taxes: Option<TaxableAccount>,
+) -> String {
+    let mut description = mode;
+    if let Some(stop) = stop {
+        description = format!("{}, {}", description, stop);
+    }
+    if taxes.is_some() {
+        description = format!("{} taxed", description);
+    }
+    description
+}
+
+fn print_returns(stats: &[StatGroup]) {
+    println!("Returns");
+    stats.iter().for_each(|stat_group| {
+        println!(
+            "{} | ER: {:.2} | Mean: {:.4} ± {:.4} (VR {:.2}x) | Annualized: {:.4} | Median: {:.4} | Ruin: {:.2}",
+            describe(&stat_group.descriptor),
+            stat_group.descriptor.expense_ratio().annual_amount(),
+            stat_group.average,
+            stat_group.standard_error,
+            stat_group.variance_reduction,
+            stat_group.annualized_average,
+            stat_group.median,
+            stat_group.ruin.probability(),
+        )
+    });
+}
+
+fn print_distributions(stats: &[StatGroup]) {
+    println!("Distribution of outcomes (percentiles every 5% up to the median)");
     stats.iter().for_each(|stat_group| {
         println!(
-            "Years: {:.1} | Leverage: {: <4.1} | Min/Max {:.4} :: {:.4} | IQR: {:.4} |  Percentiles: {:.4}",
-            stat_group.descriptor.period().as_years(),
-            stat_group.descriptor.leverage().amount(),
+            "{} | Min/Max {:.4} :: {:.4} | IQR: {:.4} | Percentiles: {:.4}",
+            describe(&stat_group.descriptor),
             stat_group.min,
             stat_group.max,
             stat_group.inner_quartile_range,
@@ -147,6 +566,415 @@ fn main() {
     });
 }
 
+fn print_ruin(stats: &[StatGroup]) {
+    println!("Ruin (share of paths by cause, average day ruined)");
+    stats.iter().for_each(|stat_group| {
+        println!(
+            "{} | Total loss: {:.2} | Terminated: {:.2} | Day: {}",
+            describe(&stat_group.descriptor),
+            stat_group.ruin.total_loss_probability(),
+            stat_group.ruin.termination_probability(),
+            stat_group
+                .ruin
+                .average_ruin_day()
+                .map_or(String::from("-"), |day| format!("{:.0}", day)),
+        )
+    });
+}
+
+fn print_trading(stats: &[StatGroup]) {
+    println!(
+        "Trading (realized leverage mean [p5, p95] and daily extremes, costs as a share of wealth, mean and p95)"
+    );
+    stats.iter().for_each(|stat_group| {
+        println!(
+            "{} | Realized: {:.2} [{:.2}, {:.2}] | Min/Max: {:.2} :: {:.2} | Costs: {:.2}, {:.2} ({:.0} trades)",
+            describe(&stat_group.descriptor),
+            stat_group.realized_leverage.mean(),
+            stat_group
+                .realized_leverage
+                .percentile(Percent::from_percent(5.0)),
+            stat_group
+                .realized_leverage
+                .percentile(Percent::from_percent(95.0)),
+            stat_group.realized_leverage.min(),
+            stat_group.realized_leverage.max(),
+            stat_group.cost_drag.mean_drag(),
+            stat_group
+                .cost_drag
+                .drag_percentile(Percent::from_percent(95.0)),
+            stat_group.cost_drag.mean_trades(),
+        )
+    });
+}
+
+/// Returns without the dividends, when they're paid on top of the daily changes, and in
+/// today's money, when there's inflation.
+fn print_price_and_real_returns(stats: &[StatGroup], config: &Config) {
+    println!("Price and real returns");
+    stats.iter().for_each(|stat_group| {
+        let mut columns = vec![describe(&stat_group.descriptor)];
+        if config.dividends().is_some() {
+            columns.push(format!(
+                "Price only: {:.4}/yr",
+                stat_group.price_return_annualized_average
+            ));
+        }
+        if config.inflation().is_some() {
+            columns.push(format!(
+                "Real: {:.4}/yr, median {:.4}",
+                stat_group.real_annualized_average, stat_group.real_median
+            ));
+        }
+        println!("{}", columns.join(" | "))
+    });
+}
+
+fn print_cash_flows(
+    price_history_variants: &[PriceHistoryVariants],
+    cash_flows: CashFlows,
+) -> Result<(), UntrackedOutcome> {
+    let mut distributions = calculate_statistic(price_history_variants, None)?
+        .map(|value: ComputedStatistic<CashFlowDistribution>| {
+            (value.descriptor(), value.statistic().clone())
+        })
+        .collect::<Vec<_>>();
+    distributions.sort_by_key(|(descriptor, _)| *descriptor);
+
+    println!(
+        "Cash flows ({:.0} initially, {:+.0} every {} days)",
+        cash_flows.initial_balance(),
+        cash_flows.amount(),
+        cash_flows.interval().as_days()
+    );
+    let format_return = |money_weighted_return: Option<Percent>| match money_weighted_return {
+        Some(money_weighted_return) => format!("{:.2}", money_weighted_return),
+        None => String::from("-"),
+    };
+    distributions.iter().for_each(|(descriptor, distribution)| {
+        println!(
+            "Years: {:.1} | Leverage: {: <4.1} | Mode: {: <11} | Ending balance: {:.0} [{:.0}, {:.0}] (median, p5, p95) | Mean: {:.0} | IRR: {} [{}, {}] | Depleted: {:.2}",
+            descriptor.period().as_years(),
+            descriptor.leverage().amount(),
+            describe_variant_mode(descriptor),
+            distribution.ending_balance_percentile(Percent::from_percent(50.0)),
+            distribution.ending_balance_percentile(Percent::from_percent(5.0)),
+            distribution.ending_balance_percentile(Percent::from_percent(95.0)),
+            distribution.mean_ending_balance(),
+            format_return(distribution.money_weighted_return_percentile(Percent::from_percent(50.0))),
+            format_return(distribution.money_weighted_return_percentile(Percent::from_percent(5.0))),
+            format_return(distribution.money_weighted_return_percentile(Percent::from_percent(95.0))),
+            distribution.depletion_probability(),
+        )
+    });
+
+    Ok(())
+}
+
+fn print_retirement(
+    price_history_variants: &[PriceHistoryVariants],
+    retirement: Retirement,
+) -> Result<(), UntrackedOutcome> {
+    let mut distributions = calculate_statistic(price_history_variants, None)?
+        .map(|value: ComputedStatistic<RetirementDistribution>| {
+            (value.descriptor(), value.statistic().clone())
+        })
+        .collect::<Vec<_>>();
+    distributions.sort_by_key(|(descriptor, _)| *descriptor);
+
+    println!(
+        "Retirement ({:.0} starting balance, {:?})",
+        retirement.starting_balance(),
+        retirement.rule()
+    );
+    distributions.iter().for_each(|(descriptor, distribution)| {
+        println!(
+            "Years: {:.1} | Leverage: {: <4.1} | Mode: {: <11} | Success: {:.2} | Terminal wealth: {:.0} [{:.0}, {:.0}] (median, p5, p95) | SWR: {:.2} @ 95%, {:.2} @ 90%",
+            descriptor.period().as_years(),
+            descriptor.leverage().amount(),
+            describe_variant_mode(descriptor),
+            distribution.success_probability(),
+            distribution.terminal_wealth_percentile(Percent::from_percent(50.0)),
+            distribution.terminal_wealth_percentile(Percent::from_percent(5.0)),
+            distribution.terminal_wealth_percentile(Percent::from_percent(95.0)),
+            distribution.safe_withdrawal_rate(Percent::from_percent(95.0)),
+            distribution.safe_withdrawal_rate(Percent::from_percent(90.0)),
+        )
+    });
+
+    Ok(())
+}
+
+fn print_taxes(
+    price_history_variants: &[PriceHistoryVariants],
+    taxes: TaxableAccount,
+) -> Result<(), UntrackedOutcome> {
+    let mut distributions = calculate_statistic(price_history_variants, None)?
+        .map(|value: ComputedStatistic<TaxDistribution>| {
+            (value.descriptor(), value.statistic().clone())
+        })
+        .filter(|(descriptor, _)| descriptor.taxes().is_some())
+        .collect::<Vec<_>>();
+    distributions.sort_by_key(|(descriptor, _)| *descriptor);
+
+    println!(
+        "Taxable account ({:.0} short-term, {:.0} long-term and {:.0} on dividends, gains realized by each mode's own trades)",
+        taxes.rates().short_term(),
+        taxes.rates().long_term(),
+        taxes.rates().dividend(),
+    );
+    distributions.iter().for_each(|(descriptor, distribution)| {
+        println!(
+            "Years: {:.1} | Leverage: {: <4.1} | Mode: {: <11} | After tax: {:.2} [{:.2}, {:.2}] (median, p5, p95) | Tax-deferred: {:.2} [{:.2}, {:.2}] | Drag: {:.2} | Taxes paid: {:.2}",
+            descriptor.period().as_years(),
+            descriptor.leverage().amount(),
+            describe_variant_mode(descriptor),
+            distribution.after_tax_wealth_percentile(Percent::from_percent(50.0)),
+            distribution.after_tax_wealth_percentile(Percent::from_percent(5.0)),
+            distribution.after_tax_wealth_percentile(Percent::from_percent(95.0)),
+            distribution.tax_deferred_wealth_percentile(Percent::from_percent(50.0)),
+            distribution.tax_deferred_wealth_percentile(Percent::from_percent(5.0)),
+            distribution.tax_deferred_wealth_percentile(Percent::from_percent(95.0)),
+            distribution.median_tax_drag(),
+            distribution.mean_taxes_paid(),
+        )
+    });
+
+    Ok(())
+}
+
+fn print_stops(price_history_variants: &[PriceHistoryVariants]) -> Result<(), UntrackedOutcome> {
+    let mut distributions = calculate_statistic(price_history_variants, None)?
+        .map(|value: ComputedStatistic<StopDistribution>| {
+            (value.descriptor(), value.statistic().clone())
+        })
+        .filter(|(descriptor, _)| descriptor.stop().is_some())
+        .collect::<Vec<_>>();
+    distributions.sort_by_key(|(descriptor, _)| *descriptor);
+
+    println!("Stops");
+    distributions.iter().for_each(|(descriptor, distribution)| {
+        let relative_outcome = |percentile| {
+            distribution
+                .relative_outcome_percentile(Percent::from_percent(percentile))
+                .map_or(String::from("n/a"), |relative| format!("{:.2}", relative))
+        };
+        println!(
+            "Years: {:.1} | Leverage: {: <4.1} | Mode: {: <11} | Triggered: {:.2} of paths, {:.2} per path | Time out: {:.2} | Whipsaw: {:.2} (p95 {:.2}) | vs holding: {} [{}, {}] (median, p5, p95)",
+            descriptor.period().as_years(),
+            descriptor.leverage().amount(),
+            describe_variant_mode(descriptor),
+            distribution.trigger_probability(),
+            distribution.mean_triggers(),
+            distribution.mean_time_out(),
+            distribution.mean_whipsaw_cost(),
+            distribution.whipsaw_cost_percentile(Percent::from_percent(95.0)),
+            relative_outcome(50.0),
+            relative_outcome(5.0),
+            relative_outcome(95.0),
+        )
+    });
+
+    Ok(())
+}
+
+/// The growth-optimal leverage of every mode and horizon, found by maximizing expected and
+/// median log wealth across the leverage grid, along with how fractions of it fare. A finer
+/// grid (e.g. `--leverage-step`) pins the optimum down more closely.
+fn print_kelly_leverages(stats: &[StatGroup], analytic_leverage: Option<Leverage>) {
+    let mut groups: BTreeMap<GridKey, Vec<&StatGroup>> = BTreeMap::new();
+    stats.iter().for_each(|stat_group| {
+        let descriptor = stat_group.descriptor;
+        let key = grid_key(&descriptor);
+        groups.entry(key).or_default().push(stat_group);
+    });
+
+    match analytic_leverage {
+        Some(leverage) => println!(
+            "Kelly leverage (analytic from input mean/variance: {:.2})",
+            leverage.amount()
+        ),
+        None => println!("Kelly leverage (no analytic optimum, the input has no variance)"),
+    }
+    groups.iter().for_each(|((period, mode, stop, taxes), group)| {
+        let leverage_of = |stat_group: &&StatGroup| stat_group.descriptor.leverage();
+        let expected_optimum = growth_optimal_leverage(
+            group
+                .iter()
+                .map(|stat_group| (leverage_of(stat_group), stat_group.log_wealth.expected())),
+        );
+        let median_optimum = growth_optimal_leverage(
+            group
+                .iter()
+                .map(|stat_group| (leverage_of(stat_group), log_wealth(stat_group.median))),
+        );
+        let format_optimum = |optimum: Option<Leverage>| match optimum {
+            Some(leverage) => format!("{:.1}", leverage.amount()),
+            None => String::from("-"),
+        };
+        println!(
+            "Years: {:.1} | Mode: {: <11} | Expected log: {} | Median log: {}",
+            period.as_years(),
+            describe_mode(*mode, *stop, *taxes),
+            format_optimum(expected_optimum),
+            format_optimum(median_optimum),
+        );
+
+        let Some(optimum) = expected_optimum else {
+            return;
+        };
+        [1.0, 0.75, 0.5, 0.25].iter().for_each(|&fraction| {
+            let target = optimum.amount() * fraction;
+            let nearest = group
+                .iter()
+                .min_by(|a, b| {
+                    let distance = |stat_group: &&&StatGroup| {
+                        (leverage_of(stat_group).amount() - target).abs()
+                    };
+                    distance(a).total_cmp(&distance(b))
+                })
+                .unwrap();
+            println!(
+                "    {:.2} Kelly -> Leverage: {: <4.1} | Growth: {:.4}/yr | Median: {:.4} | P5: {:.4} | Min: {:.4} | Ruin: {:.2}",
+                fraction,
+                leverage_of(nearest).amount(),
+                nearest.log_wealth.annualized_growth(*period),
+                nearest.median,
+                // Percentiles are taken every 5%
+                nearest.percentiles[1],
+                nearest.min,
+                nearest.ruin.probability(),
+            );
+        });
+    });
+}
+
+/// The leverage that meets a required probability of some outcome for every mode and horizon,
+/// with the band it could lie in given sampling error.
+fn print_target_leverages(
+    price_history_variants: &[PriceHistoryVariants],
+    descriptors: &[PriceHistoryDescriptor],
+    period: Period,
+) -> Result<(), UntrackedOutcome> {
+    let solve =
+        |question: &str, target: OutcomeTarget, predicate: fn(PriceChange, Period) -> bool| {
+            let contexts: HashMap<PriceHistoryDescriptor, MatchingPriceChangeRatioContext> =
+                descriptors
+                    .iter()
+                    .map(|&descriptor| {
+                        let context = MatchingPriceChangeRatioContext::new(
+                            move |price_change: PriceChange| predicate(price_change, period),
+                        );
+                        (descriptor, context)
+                    })
+                    .collect();
+
+            let mut groups: BTreeMap<GridKey, Vec<_>> = BTreeMap::new();
+            calculate_statistic(price_history_variants, Some(&contexts))?.for_each(
+                |value: ComputedStatistic<MatchingPriceChangeRatio>| {
+                    let descriptor = value.descriptor();
+                    groups
+                        .entry(grid_key(&descriptor))
+                        .or_default()
+                        .push((descriptor.leverage(), *value.statistic()));
+                },
+            );
+
+            println!("{}", question);
+            groups
+                .iter()
+                .for_each(|((period, mode, stop, taxes), ratios)| {
+                    let solution = target.solve(ratios);
+                    let format_leverage = |leverage: Option<f64>| match leverage {
+                        Some(leverage) => format!("{:.2}", leverage),
+                        None => String::from("-"),
+                    };
+                    println!(
+                        "Years: {:.1} | Mode: {: <11} | Leverage: {} [{}, {}]",
+                        period.as_years(),
+                        describe_mode(*mode, *stop, *taxes),
+                        format_leverage(solution.estimate()),
+                        format_leverage(solution.low()),
+                        format_leverage(solution.high()),
+                    );
+                });
+            Ok(())
+        };
+
+    solve(
+        "Highest leverage with a 75% chance of an annualized return of at least 5% (95% band)",
+        OutcomeTarget::new(LeverageBound::Highest, Percent::from_percent(75.0), 1.96),
+        |price_change, period| {
+            price_change.annualized_return(period).percent_change() >= Percent::from_percent(5.0)
+        },
+    )?;
+    solve(
+        "Lowest leverage with a 50% chance of turning $1M into $2M (95% band)",
+        OutcomeTarget::new(LeverageBound::Lowest, Percent::from_percent(50.0), 1.96),
+        |price_change, _| price_change.percent_change() >= Percent::from_percent(100.0),
+    )
+}
+
+fn print_control_variate_estimates(
+    price_history_variants: &[PriceHistoryVariants],
+    path_sampling: PathSampling,
+    descriptors: &[PriceHistoryDescriptor],
+    expected_control: PriceChange,
+    period: Period,
+) -> Result<(), UntrackedOutcome> {
+    let estimate = |context: &dyn Fn() -> ControlVariateContext| {
+        let contexts: HashMap<PriceHistoryDescriptor, ControlVariateContext> = descriptors
+            .iter()
+            .map(|&descriptor| (descriptor, context()))
+            .collect();
+
+        Ok(
+            calculate_grouped_statistic(price_history_variants, path_sampling, Some(&contexts))?
+                .map(|value: ComputedStatistic<ControlVariateEstimate>| {
+                    (value.descriptor(), *value.statistic())
+                })
+                .collect::<HashMap<_, _>>(),
+        )
+    };
+
+    let averages = estimate(&|| ControlVariateContext::average(expected_control))?;
+    let probabilities = estimate(&|| {
+        ControlVariateContext::probability(expected_control, move |price_change: PriceChange| {
+            price_change.annualized_return(period).percent_change() >= Percent::from_percent(15.0)
+        })
+    })?;
+
+    println!(
+        "Control variates (underlying expected total: {:.4})",
+        expected_control
+    );
+
+    let mut descriptors = Vec::from(descriptors);
+    descriptors.sort();
+    descriptors.iter().for_each(|descriptor| {
+        let average = &averages[descriptor];
+        let probability = &probabilities[descriptor];
+        println!(
+            "Years: {:.1} | Leverage: {: <4.1} | Mode: {: <11} | Mean: {:.4} ± {:.4} -> {:.4} ± {:.4} (VR {:.2}x) | P(Annualized >= 15%): {:.2} ± {:.2} -> {:.2} ± {:.2} (VR {:.2}x)",
+            descriptor.period().as_years(),
+            descriptor.leverage().amount(),
+            describe_variant_mode(descriptor),
+            average.naive_estimate(),
+            average.naive_standard_error(),
+            average.estimate(),
+            average.standard_error(),
+            average.variance_reduction(),
+            probability.naive_estimate(),
+            probability.naive_standard_error(),
+            probability.estimate(),
+            probability.standard_error(),
+            probability.variance_reduction(),
+        )
+    });
+
+    Ok(())
+}
+
 #[derive(Debug, Clone)]
 struct StatGroup {
     descriptor: PriceHistoryDescriptor,
@@ -154,12 +982,22 @@ struct StatGroup {
     annualized_average: PriceChange,
     stdev: PriceChange,
     median: PriceChange,
+    price_return_annualized_average: PriceChange,
+    real_annualized_average: PriceChange,
+    real_median: PriceChange,
     min: PriceChange,
     max: PriceChange,
     inner_quartile_range: PriceChange,
+    standard_error: PriceChange,
+    variance_reduction: f64,
+    ruin: RuinProbability,
+    realized_leverage: RealizedLeverageDistribution,
+    cost_drag: CostDragDistribution,
+    log_wealth: LogWealth,
     percentiles: Vec<PriceChange>,
 }
 
+#[allow(dead_code)]
 impl StatGroup {
     fn sharpe_ratio(&self) -> f64 {
         self.average.percent_change().as_decimal() / self.stdev.percent_change().as_decimal()
@@ -1,11 +1,13 @@
 use std::{
+    cmp::Ordering,
     fmt::Display,
+    hash::Hash,
     ops::{self, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
 };
 
 use ops::Add;
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, Copy)]
 pub struct Percent(f64);
 
 #[allow(dead_code)]
@@ -55,6 +57,39 @@ impl Percent {
             .fold(1.0, f64::mul);
         Self::from_multiplier(composed_multiplier)
     }
+
+    // Adding zero turns -0 into 0, so both zeros compare and hash the same
+    fn comparable(&self) -> f64 {
+        self.0 + 0.0
+    }
+}
+
+// Percents are compared with the IEEE total order so they can be used as keys and sorted
+// directly. Equality, ordering and hashing all agree, even for NaN.
+impl PartialEq for Percent {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Percent {}
+
+impl PartialOrd for Percent {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Percent {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.comparable().total_cmp(&other.comparable())
+    }
+}
+
+impl Hash for Percent {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.comparable().to_bits().hash(state);
+    }
 }
 
 impl Display for Percent {
@@ -113,3 +148,29 @@ impl SubAssign<Percent> for Percent {
         self.0 -= rhs.0;
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::hash_map::DefaultHasher;
+
+    use super::*;
+
+    #[test]
+    fn test_comparisons_agree() {
+        let hash = |percent: Percent| {
+            let mut hasher = DefaultHasher::new();
+            percent.hash(&mut hasher);
+            std::hash::Hasher::finish(&hasher)
+        };
+
+        let zero = Percent::from_decimal(0.0);
+        let negative_zero = Percent::from_decimal(-0.0);
+        assert_eq!(zero, negative_zero);
+        assert_eq!(hash(zero), hash(negative_zero));
+
+        let nan = Percent::from_decimal(f64::NAN);
+        assert_eq!(nan, nan);
+        assert_eq!(nan.cmp(&nan), Ordering::Equal);
+        assert!(Percent::from_percent(1.0) < Percent::from_percent(2.0));
+    }
+}
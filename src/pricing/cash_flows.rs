@@ -0,0 +1,189 @@
+use crate::number::Percent;
+
+use super::{Period, PriceHistory, Ruin, RuinCause};
+
+/// Money put into or taken out of a position on a schedule, starting from an initial
+/// balance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CashFlows {
+    initial_balance: f64,
+    amount: f64,
+    interval: Period,
+}
+
+/// Where a path's balance ended up once its cash flows were applied.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CashFlowOutcome {
+    ending_balance: f64,
+    money_weighted_return: Option<Percent>,
+    depleted: bool,
+}
+
+impl CashFlowOutcome {
+    pub fn ending_balance(&self) -> f64 {
+        self.ending_balance
+    }
+
+    /// The annualized internal rate of return of every flow in and out, including the
+    /// ending balance. `None` if no rate balances them.
+    pub fn money_weighted_return(&self) -> Option<Percent> {
+        self.money_weighted_return
+    }
+
+    /// Whether withdrawals ran the balance down to nothing.
+    pub fn depleted(&self) -> bool {
+        self.depleted
+    }
+}
+
+impl CashFlows {
+    /// `amount` is contributed at the close of every `interval`, or withdrawn if negative.
+    pub fn new(initial_balance: f64, amount: f64, interval: Period) -> Self {
+        if initial_balance < 0.0 || interval.as_days() == 0 {
+            panic!(
+                "Invalid cash flows: {} initially, every {} days",
+                initial_balance,
+                interval.as_days()
+            );
+        }
+
+        Self {
+            initial_balance,
+            amount,
+            interval,
+        }
+    }
+
+    pub fn initial_balance(&self) -> f64 {
+        self.initial_balance
+    }
+
+    pub fn amount(&self) -> f64 {
+        self.amount
+    }
+
+    pub fn interval(&self) -> Period {
+        self.interval
+    }
+
+    /// Runs the balance along a path's daily changes with flows at the scheduled closes.
+    /// Once the path is ruined whatever is left sits in cash, so flows carry on but the
+    /// balance no longer moves with the market. A withdrawal takes at most what's left.
+    pub fn apply(&self, price_history: &PriceHistory, ruin: Option<Ruin>) -> CashFlowOutcome {
+        let interval = self.interval.as_days();
+        // Flows from the investor's side, by the close they happen at
+        let mut flows = vec![(0, -self.initial_balance)];
+        let mut balance = self.initial_balance;
+        let mut depleted = false;
+
+        for (day, price_change) in price_history.iter().enumerate() {
+            let invested = ruin.is_none_or(|ruin| day <= ruin.day());
+            if invested {
+                balance *= price_change.percent_change().as_multiplier();
+            }
+            if ruin.is_some_and(|ruin| ruin.day() == day && ruin.cause() == RuinCause::TotalLoss) {
+                balance = 0.0;
+            }
+
+            if (day as u64 + 1) % interval == 0 {
+                let flow = f64::max(self.amount, -balance);
+                balance += flow;
+                flows.push((day + 1, -flow));
+                depleted |= self.amount < 0.0 && balance <= 0.0;
+            }
+        }
+
+        let days = price_history.iter().len();
+        flows.push((days, balance));
+        CashFlowOutcome {
+            ending_balance: balance,
+            money_weighted_return: internal_rate_of_return(&flows),
+            depleted,
+        }
+    }
+}
+
+/// The annualized rate at which the flows, each given by the close it happens at, are
+/// worth nothing today. Solved over daily log growth rates with Newton's method, falling
+/// back to bisection whenever a step would leave the bracket around the root.
+fn internal_rate_of_return(flows: &[(usize, f64)]) -> Option<Percent> {
+    // The present value and its derivative at a daily log growth rate
+    let present_value = |growth: f64| {
+        flows
+            .iter()
+            .fold((0.0, 0.0), |(value, slope), &(day, flow)| {
+                let discounted = flow * f64::exp(-growth * day as f64);
+                (value + discounted, slope - day as f64 * discounted)
+            })
+    };
+
+    // Between about -100% and +300,000% a year
+    let (mut low, mut high) = (-0.05, 0.05);
+    if present_value(low).0 < 0.0 {
+        // Every rate leaves the investor behind, e.g. when everything was lost
+        return Some(Percent::from_percent(-100.0));
+    }
+    if present_value(high).0 > 0.0 {
+        return None;
+    }
+
+    let mut growth = 0.0;
+    for _ in 0..100 {
+        let (value, slope) = present_value(growth);
+        match value > 0.0 {
+            true => low = growth,
+            false => high = growth,
+        }
+        let newton = growth - value / slope;
+        let next = match newton > low && newton < high {
+            true => newton,
+            false => (low + high) / 2.0,
+        };
+        if (next - growth).abs() < 1e-14 {
+            break;
+        }
+        growth = next;
+    }
+
+    let days_per_year = Period::MARKET_DAYS_PER_YEAR as f64;
+    Some(Percent::from_multiplier(f64::exp(growth * days_per_year)))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pricing::PriceChange;
+
+    use super::*;
+
+    #[test]
+    fn test_cash_flows() {
+        let price_history = [10.0, 0.0, -50.0, 0.0]
+            .iter()
+            .map(|&change| PriceChange::from(Percent::from_percent(change)))
+            .collect::<PriceHistory>();
+
+        // Contributing 100 every other day: (100 * 1.1 + 100) * 0.5 + 100
+        let contributions =
+            CashFlows::new(100.0, 100.0, Period::Days(2)).apply(&price_history, None);
+        assert!((contributions.ending_balance() - 205.0).abs() < 1e-9);
+        assert!(!contributions.depleted());
+
+        // Withdrawing 100 every other day runs out on the second withdrawal
+        let withdrawals =
+            CashFlows::new(150.0, -100.0, Period::Days(2)).apply(&price_history, None);
+        assert_eq!(withdrawals.ending_balance(), 0.0);
+        assert!(withdrawals.depleted());
+
+        // Doubling in a year with no flows in between is a 100% money-weighted return
+        let year = Period::Years(1).as_days() as usize;
+        let doubling = CashFlows::new(100.0, 0.0, Period::Years(2));
+        let price_history = std::iter::once(PriceChange::from(Percent::from_percent(100.0)))
+            .chain(std::iter::repeat_n(PriceChange::zero(), year - 1))
+            .collect::<PriceHistory>();
+        let irr = doubling
+            .apply(&price_history, None)
+            .money_weighted_return()
+            .unwrap();
+        assert!((irr.as_percent() - 100.0).abs() < 1e-6);
+    }
+}
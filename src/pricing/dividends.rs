@@ -0,0 +1,29 @@
+use crate::number::Percent;
+
+use super::{rate_series::to_daily_rate, PathSampling, Period, PriceChange, PriceHistory};
+
+/// What the index pays out on top of its daily price changes, when those are price-only.
+/// Leave dividends out when the daily changes are already total returns.
+#[derive(Debug, Clone)]
+pub enum Dividends {
+    /// An annual yield, paid a little every day.
+    Yield(Percent),
+}
+
+impl Dividends {
+    /// The daily dividend yield of the index for every path of a draw.
+    pub fn price_histories(
+        &self,
+        path_sampling: PathSampling,
+        period: Period,
+    ) -> Vec<PriceHistory> {
+        match self {
+            Dividends::Yield(annual_yield) => {
+                let daily_yield = PriceChange::from(to_daily_rate(*annual_yield));
+                let dividends = std::iter::repeat_n(daily_yield, period.as_days() as usize)
+                    .collect::<PriceHistory>();
+                vec![dividends; path_sampling.paths_per_draw() as usize]
+            }
+        }
+    }
+}
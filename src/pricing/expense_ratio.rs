@@ -1,33 +1,133 @@
 use crate::number::Percent;
 
-use super::Period;
+use super::{Leverage, Period};
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ExpenseRatio {
+    annual_amount: Percent,
     amount: Percent,
 }
 
 #[allow(dead_code)]
 impl ExpenseRatio {
+    /// An expense ratio charging `amount` over each `period`, as a compounding daily fee.
     pub fn new(amount: Percent, period: Period) -> Self {
-        let amount_per_period =
-            -Percent::from_decimal(amount.as_decimal() / period.as_days() as f64);
+        let remaining_multiplier = (-amount).as_multiplier();
+        let daily_multiplier = remaining_multiplier.powf(1.0 / period.as_days() as f64);
+        let annual_multiplier = remaining_multiplier.powf(1.0 / period.as_years());
+
         ExpenseRatio {
-            amount: amount_per_period,
+            annual_amount: Percent::zero() - Percent::from_multiplier(annual_multiplier),
+            amount: Percent::zero() + Percent::from_multiplier(daily_multiplier),
         }
     }
 
     pub fn zero() -> Self {
         ExpenseRatio {
+            annual_amount: Percent::zero(),
             amount: Percent::zero(),
         }
     }
 
+    /// The fee charged over a year.
+    pub fn annual_amount(&self) -> Percent {
+        self.annual_amount
+    }
+
+    /// The (negative) change applied each day.
     pub fn amount(&self) -> Percent {
         self.amount
     }
 
     pub fn multiplier(&self) -> f64 {
-        1.0 - self.amount.as_decimal()
+        self.amount.as_multiplier()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleInterpolation {
+    /// Interpolate linearly between neighbouring points.
+    Linear,
+    /// Charge the ratio of the first point at or above the leverage, as if holding the
+    /// cheapest product that offers at least that much leverage. For inverse leverage the
+    /// search runs the other way, to the first point at or below it.
+    Ceiling,
+}
+
+/// Annual expense ratios for a set of leverage levels, either for specific products or as
+/// a piecewise schedule. Leverage beyond either end uses the ratio of the nearest point.
+#[derive(Debug, Clone)]
+pub struct ExpenseRatioSchedule {
+    points: Vec<(Leverage, Percent)>,
+    interpolation: ScheduleInterpolation,
+}
+
+impl ExpenseRatioSchedule {
+    pub fn new(points: &[(Leverage, Percent)], interpolation: ScheduleInterpolation) -> Self {
+        if points.is_empty() {
+            panic!("An expense ratio schedule needs at least one point");
+        }
+
+        let mut points = Vec::from(points);
+        points.sort_by_key(|&(leverage, _)| leverage);
+        Self {
+            points,
+            interpolation,
+        }
+    }
+
+    pub fn flat(annual_amount: Percent) -> Self {
+        Self::new(
+            &[(Leverage::new(1.0), annual_amount)],
+            ScheduleInterpolation::Linear,
+        )
+    }
+
+    pub fn annual_amount(&self, leverage: Leverage) -> Percent {
+        if leverage.is_inverse() && self.interpolation == ScheduleInterpolation::Ceiling {
+            let lower = self
+                .points
+                .iter()
+                .rev()
+                .find(|&&(point_leverage, _)| point_leverage <= leverage);
+            return lower.unwrap_or(&self.points[0]).1;
+        }
+
+        let upper = self
+            .points
+            .iter()
+            .position(|&(point_leverage, _)| point_leverage >= leverage);
+
+        match (upper, self.interpolation) {
+            (None, _) => self.points[self.points.len() - 1].1,
+            (Some(0), _) => self.points[0].1,
+            (Some(i), ScheduleInterpolation::Ceiling) => self.points[i].1,
+            (Some(i), ScheduleInterpolation::Linear) => {
+                let (low_leverage, low_amount) = self.points[i - 1];
+                let (high_leverage, high_amount) = self.points[i];
+                let fraction = (leverage.amount() - low_leverage.amount())
+                    / (high_leverage.amount() - low_leverage.amount());
+                low_amount + (high_amount - low_amount) * Percent::from_decimal(fraction)
+            }
+        }
+    }
+
+    pub fn expense_ratio(&self, leverage: Leverage) -> ExpenseRatio {
+        ExpenseRatio::new(self.annual_amount(leverage), Period::Years(1))
+    }
+}
+
+impl Default for ExpenseRatioSchedule {
+    /// Nothing from 0x up to 1x, and the 0.93% typical of leveraged and inverse funds
+    /// beyond that.
+    fn default() -> Self {
+        Self::new(
+            &[
+                (Leverage::new(-1.0), Percent::from_percent(0.93)),
+                (Leverage::new(1.0), Percent::zero()),
+                (Leverage::new(2.0), Percent::from_percent(0.93)),
+            ],
+            ScheduleInterpolation::Ceiling,
+        )
     }
 }
@@ -0,0 +1,54 @@
+use crate::number::Percent;
+
+use super::{rate_series::to_daily_rate, Period, RateSeries};
+
+/// What a leveraged fund pays to borrow: a financing rate series plus the spread its swap
+/// counterparties charge on top.
+#[derive(Debug, Clone)]
+pub struct Financing {
+    rate_series: RateSeries,
+    swap_spread: Percent,
+}
+
+impl Financing {
+    pub fn new(rate_series: RateSeries, swap_spread: Percent) -> Self {
+        Self {
+            rate_series,
+            swap_spread,
+        }
+    }
+
+    pub fn swap_spread(&self) -> Percent {
+        self.swap_spread
+    }
+
+    /// The daily financing rate on every day of a path, without the swap spread. A broker
+    /// loan is priced off these, with its own spread on top.
+    pub fn daily_base_rates(&self, path: u64, period: Period) -> Vec<Percent> {
+        self.rate_series
+            .annual_rates(path, period)
+            .into_iter()
+            .map(to_daily_rate)
+            .collect()
+    }
+
+    /// The daily cost of each unit borrowed on every day of a path.
+    pub fn daily_borrowing_rates(&self, path: u64, period: Period) -> Vec<Percent> {
+        self.rate_series
+            .annual_rates(path, period)
+            .into_iter()
+            .map(|rate| to_daily_rate(rate + self.swap_spread))
+            .collect()
+    }
+}
+
+/// The share of a fund's equity paid in financing over a day at `leverage`. A leveraged fund
+/// pays the borrowing rate on what it borrows beyond its equity. An inverse fund's swaps
+/// short the whole exposure, and its counterparties charge the swap spread (the borrowing
+/// rate over the base rate) on all of it, as a broker would charge a borrow fee.
+pub fn daily_financing_cost(leverage: f64, borrowing_rate: Percent, base_rate: Percent) -> f64 {
+    match leverage < 0.0 {
+        true => -leverage * (borrowing_rate - base_rate).as_decimal(),
+        false => f64::max(leverage - 1.0, 0.0) * borrowing_rate.as_decimal(),
+    }
+}
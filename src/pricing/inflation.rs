@@ -0,0 +1,29 @@
+use super::{PathSampling, Period, PriceChange, PriceHistory, RateSeries};
+
+/// Where the rise in consumer prices on each simulated day comes from.
+#[derive(Debug, Clone)]
+pub enum Inflation {
+    /// Annual inflation rates: constant, read in order, or simulated on their own.
+    Rates(RateSeries),
+}
+
+impl Inflation {
+    /// The daily change in consumer prices for every path of a draw.
+    pub fn price_histories(
+        &self,
+        path_sampling: PathSampling,
+        draw: u64,
+        period: Period,
+    ) -> Vec<PriceHistory> {
+        match self {
+            Inflation::Rates(rates) => {
+                let inflation = rates
+                    .daily_rates(draw, period)
+                    .into_iter()
+                    .map(PriceChange::from)
+                    .collect::<PriceHistory>();
+                vec![inflation; path_sampling.paths_per_draw() as usize]
+            }
+        }
+    }
+}
@@ -7,25 +7,30 @@ const FIXED_POINT_MULTIPLIER: f64 = 1000.0;
 #[derive(Debug, Clone, Copy)]
 pub struct Leverage {
     amount: f64,
-    comparable_amount: u32,
+    comparable_amount: i32,
 }
 
 #[allow(dead_code)]
 impl Leverage {
     pub fn new(amount: f64) -> Self {
-        if amount < 0.0 {
+        if !amount.is_finite() {
             panic!("Inavlid leverage amount: {}", amount);
         }
 
         Leverage {
             amount,
-            comparable_amount: (amount * FIXED_POINT_MULTIPLIER).round() as u32,
+            comparable_amount: (amount * FIXED_POINT_MULTIPLIER).round() as i32,
         }
     }
 
     pub fn amount(&self) -> f64 {
         self.amount
     }
+
+    /// Whether the position gains when the underlying falls.
+    pub fn is_inverse(&self) -> bool {
+        self.comparable_amount < 0
+    }
 }
 
 impl PartialOrd for Leverage {
@@ -0,0 +1,128 @@
+use super::Leverage;
+
+/// The leverage amounts to build variants for, kept sorted and without duplicates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeverageGrid {
+    leverages: Vec<Leverage>,
+}
+
+impl LeverageGrid {
+    pub fn new(leverages: &[Leverage]) -> Self {
+        let mut leverages = Vec::from(leverages);
+        leverages.sort();
+        leverages.dedup();
+        Self { leverages }
+    }
+
+    /// Every `step` from `start` up to and including `end`.
+    pub fn range(start: f64, end: f64, step: f64) -> Self {
+        if step <= 0.0 || end < start {
+            panic!("Invalid leverage range: {} to {} by {}", start, end, step);
+        }
+
+        // Count steps rather than accumulating them, so the grid doesn't drift
+        let steps = ((end - start) / step + 1e-9).floor() as usize;
+        let leverages: Vec<Leverage> = (0..=steps)
+            .map(|i| start + i as f64 * step)
+            .map(|amount| Leverage::new((amount * 1e6).round() / 1e6))
+            .collect();
+        Self::new(&leverages)
+    }
+
+    /// Every leverage in either grid.
+    pub fn merge(&self, other: &LeverageGrid) -> Self {
+        Self::new(&[self.leverages.as_slice(), other.leverages.as_slice()].concat())
+    }
+
+    pub fn leverages(&self) -> &[Leverage] {
+        &self.leverages
+    }
+}
+
+impl Default for LeverageGrid {
+    /// A few inverse funds, a fine grid up to 4x and a coarse one beyond.
+    fn default() -> Self {
+        LeverageGrid::range(-3.0, -1.0, 1.0)
+            .merge(&LeverageGrid::range(0.1, 4.0, 0.1))
+            .merge(&LeverageGrid::range(4.5, 5.0, 0.5))
+            .merge(&LeverageGrid::range(6.0, 10.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_default_grid() {
+        let expected = vec![
+            Leverage::new(-3.0),
+            Leverage::new(-2.0),
+            Leverage::new(-1.0),
+            Leverage::new(0.1),
+            Leverage::new(0.2),
+            Leverage::new(0.3),
+            Leverage::new(0.4),
+            Leverage::new(0.5),
+            Leverage::new(0.6),
+            Leverage::new(0.7),
+            Leverage::new(0.8),
+            Leverage::new(0.9),
+            Leverage::new(1.0),
+            Leverage::new(1.1),
+            Leverage::new(1.2),
+            Leverage::new(1.3),
+            Leverage::new(1.4),
+            Leverage::new(1.5),
+            Leverage::new(1.6),
+            Leverage::new(1.7),
+            Leverage::new(1.8),
+            Leverage::new(1.9),
+            Leverage::new(2.0),
+            Leverage::new(2.1),
+            Leverage::new(2.2),
+            Leverage::new(2.3),
+            Leverage::new(2.4),
+            Leverage::new(2.5),
+            Leverage::new(2.6),
+            Leverage::new(2.7),
+            Leverage::new(2.8),
+            Leverage::new(2.9),
+            Leverage::new(3.0),
+            Leverage::new(3.1),
+            Leverage::new(3.2),
+            Leverage::new(3.3),
+            Leverage::new(3.4),
+            Leverage::new(3.5),
+            Leverage::new(3.6),
+            Leverage::new(3.7),
+            Leverage::new(3.8),
+            Leverage::new(3.9),
+            Leverage::new(4.0),
+            Leverage::new(4.5),
+            Leverage::new(5.0),
+            Leverage::new(6.0),
+            Leverage::new(7.0),
+            Leverage::new(8.0),
+            Leverage::new(9.0),
+            Leverage::new(10.0),
+        ];
+        let actual = Vec::from(LeverageGrid::default().leverages());
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_range() {
+        let grid = LeverageGrid::range(1.5, 2.5, 0.05);
+        assert_eq!(grid.leverages().len(), 21);
+        assert_eq!(grid.leverages()[7], Leverage::new(1.85));
+        assert_eq!(grid.leverages()[20], Leverage::new(2.5));
+
+        let merged = grid.merge(&LeverageGrid::new(&[
+            Leverage::new(2.0),
+            Leverage::new(3.0),
+        ]));
+        assert_eq!(merged.leverages().len(), 22);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{
+    number::Percent,
+    pricing::{rate_series::to_daily_rate, ExpenseRatio, Leverage, MarketHistory, PriceHistory},
+};
+
+use super::{Activity, CostDrag, RealizedLeverage, TradingCosts};
+
+/// A self-managed leveraged position per unit of starting equity: the holding of the
+/// underlying, what was borrowed to buy it, and any equity left over as cash.
+#[derive(Debug, Clone, Copy)]
+pub struct Account {
+    position: f64,
+    loan: f64,
+    cash: f64,
+    // Notional traded since the last close
+    traded: f64,
+}
+
+impl Account {
+    pub fn new(leverage: Leverage) -> Self {
+        let mut account = Account {
+            position: 0.0,
+            loan: 0.0,
+            cash: 1.0,
+            traded: 0.0,
+        };
+        account.set_position(leverage.amount());
+        account
+    }
+
+    pub fn position(&self) -> f64 {
+        self.position
+    }
+
+    pub fn equity(&self) -> f64 {
+        self.position + self.cash - self.loan
+    }
+
+    /// Whether anything is borrowed, either cash to buy with or shares to sell short.
+    pub fn is_borrowing(&self) -> bool {
+        self.loan > 0.0 || self.position < 0.0
+    }
+
+    /// Buys or sells the underlying to hold `position`, borrowing whatever the equity
+    /// doesn't cover. A negative position is a short, with the proceeds held as cash.
+    pub fn set_position(&mut self, position: f64) {
+        let equity = self.equity();
+        self.traded += (position - self.position).abs();
+        self.position = position;
+        self.loan = f64::max(position - equity, 0.0);
+        self.cash = f64::max(equity - position, 0.0);
+    }
+
+    /// Pays out of cash, borrowing whatever it doesn't cover.
+    fn pay(&mut self, amount: f64) {
+        let from_cash = f64::min(amount, self.cash);
+        self.cash -= from_cash;
+        self.loan += amount - from_cash;
+    }
+
+    /// The exposure to the underlying per unit of equity.
+    pub fn leverage(&self) -> f64 {
+        self.position / self.equity()
+    }
+
+    /// Runs the account over a path. The position is in shares of the underlying, so it's
+    /// paid the index's dividends in full, or pays them on a short. After each day's market
+    /// move, interest on the loan and on any cash, `manage` gets to trade before the next day
+    /// starts, paying `trading_costs` on whatever it trades. The loan accrues the path's base
+    /// rates plus `loan_spread`. Once equity is gone the account stays closed. Whatever
+    /// `manage` sells of the holding, and the dividends it's paid, go into the activity.
+    pub fn simulate<F>(
+        mut self,
+        expense_ratio: ExpenseRatio,
+        loan_spread: Percent,
+        trading_costs: &TradingCosts,
+        market_history: &MarketHistory,
+        mut manage: F,
+    ) -> (PriceHistory, RealizedLeverage, CostDrag, Activity)
+    where
+        F: FnMut(usize, &mut Account),
+    {
+        let daily_spread = to_daily_rate(loan_spread).as_decimal();
+        let base_rates = market_history.base_rates();
+        let dividends = market_history.dividends(0);
+        let cash_rates = market_history.cash_rates();
+        let mut equity = self.equity();
+        let mut realized_leverage = RealizedLeverage::none();
+        let trading_costs = trading_costs.along(market_history.price_history());
+        let mut cost_drag = CostDrag::none();
+        let mut activity = Activity::none();
+
+        let price_history = market_history
+            .price_history()
+            .iter()
+            .enumerate()
+            .map(|(day, price_change)| {
+                if equity <= 0.0 {
+                    return Percent::zero().into();
+                }
+
+                realized_leverage.record(self.leverage());
+                let dividend = dividends.map_or(0.0, |dividends| {
+                    dividends[day].percent_change().as_decimal()
+                });
+                let price_multiplier = price_change.percent_change().as_multiplier();
+                // Shorts pay the dividends rather than receive them
+                let paid = f64::max(self.position * price_multiplier * dividend, 0.0);
+                self.position *= price_multiplier * (1.0 + dividend) * expense_ratio.multiplier();
+                self.loan *= 1.0 + base_rates[day].as_decimal() + daily_spread;
+                if let Some(cash_rates) = cash_rates {
+                    self.cash *= 1.0 + cash_rates[day].as_decimal();
+                }
+
+                let previous_equity = equity;
+                equity = self.equity();
+                if equity > 0.0 {
+                    activity.record_dividend(day, paid / equity);
+                    self.traded = 0.0;
+                    let held = self.position;
+                    manage(day, &mut self);
+                    activity.record_sale(day, sold_share(held, self.position));
+                    let cost = f64::min(trading_costs.cost(day, self.traded), equity);
+                    if cost > 0.0 {
+                        self.pay(cost);
+                        cost_drag.record(cost, equity);
+                        equity = self.equity();
+                    }
+                }
+
+                Percent::from_multiplier(f64::max(equity, 0.0) / previous_equity).into()
+            })
+            .collect();
+
+        (price_history, realized_leverage, cost_drag, activity)
+    }
+}
+
+/// The share of a holding sold in going from `held` to `position`. Turning a long into a
+/// short, or the other way around, sells all of it.
+fn sold_share(held: f64, position: f64) -> f64 {
+    if held == 0.0 {
+        0.0
+    } else if held.signum() != position.signum() {
+        1.0
+    } else {
+        f64::max(1.0 - position / held, 0.0)
+    }
+}
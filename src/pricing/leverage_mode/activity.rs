@@ -0,0 +1,46 @@
+/// What the holder of a position sold and was paid along a path, which is what a taxable
+/// account holding it is taxed on.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Activity {
+    // Share of the holding sold at each close, and dividends received each day as a share
+    // of the equity at its close. Both only run up to the last day with any.
+    sold: Vec<f64>,
+    dividends: Vec<f64>,
+}
+
+impl Activity {
+    /// A holding that's never sold and pays nothing.
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    pub fn record_sale(&mut self, day: usize, share: f64) {
+        // Leaves out the dust of rebalancing to where the position already is
+        if share > 1e-12 {
+            record(&mut self.sold, day, f64::min(share, 1.0));
+        }
+    }
+
+    pub fn record_dividend(&mut self, day: usize, share: f64) {
+        if share > 0.0 {
+            record(&mut self.dividends, day, share);
+        }
+    }
+
+    /// The share of the holding sold at the close of `day`.
+    pub fn sold(&self, day: usize) -> f64 {
+        self.sold.get(day).copied().unwrap_or(0.0)
+    }
+
+    /// Dividends received on `day`, as a share of the equity at its close.
+    pub fn dividend(&self, day: usize) -> f64 {
+        self.dividends.get(day).copied().unwrap_or(0.0)
+    }
+}
+
+fn record(values: &mut Vec<f64>, day: usize, value: f64) {
+    if values.len() <= day {
+        values.resize(day + 1, 0.0);
+    }
+    values[day] += value;
+}
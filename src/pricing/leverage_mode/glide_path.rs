@@ -0,0 +1,253 @@
+use std::fmt::Display;
+
+use crate::{
+    number::Percent,
+    pricing::{ExpenseRatio, Leverage, MarketHistory, Period, PriceHistory},
+};
+
+use super::{
+    account::Account, Activity, CostDrag, RealizedLeverage, RebalanceSchedule, TradingCosts,
+};
+
+const MAX_STEPS: usize = 4;
+
+/// From the start of `year` of the path on, `leverage` is held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GlideStep {
+    year: u64,
+    leverage: Leverage,
+}
+
+impl GlideStep {
+    pub fn new(year: u64, leverage: Leverage) -> Self {
+        Self { year, leverage }
+    }
+
+    pub fn year(&self) -> u64 {
+        self.year
+    }
+
+    pub fn leverage(&self) -> Leverage {
+        self.leverage
+    }
+}
+
+/// How the leverage held changes over the life of a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GlideSchedule {
+    /// A straight line from `start` on the first day to `end` on the last.
+    Linear { start: Leverage, end: Leverage },
+    /// Each step's leverage until the next step's year, with the first step's held before
+    /// it. Built with `GlideSchedule::steps`.
+    // Fixed capacity so the schedule can live in a descriptor
+    Steps([Option<GlideStep>; MAX_STEPS]),
+    /// A rule of thumb like "120 minus your age" in stocks: `base` minus the investor's age,
+    /// as a percentage, for an investor `starting_age` at the start of the path. Kept
+    /// within `min_leverage` and `max_leverage`.
+    AgeBased {
+        starting_age: u64,
+        base: u64,
+        min_leverage: Leverage,
+        max_leverage: Leverage,
+    },
+}
+
+impl GlideSchedule {
+    pub fn steps(steps: &[GlideStep]) -> Self {
+        if steps.is_empty() || steps.len() > MAX_STEPS {
+            panic!(
+                "A step schedule has 1 to {} steps, got {}",
+                MAX_STEPS,
+                steps.len()
+            );
+        }
+        if !steps.is_sorted_by_key(|step| step.year()) {
+            panic!("Glide steps must be in order of their years");
+        }
+
+        let mut slots = [None; MAX_STEPS];
+        steps
+            .iter()
+            .zip(slots.iter_mut())
+            .for_each(|(&step, slot)| *slot = Some(step));
+        GlideSchedule::Steps(slots)
+    }
+
+    /// The leverage to hold over `day` of a path `days` long.
+    pub fn leverage_on(&self, day: usize, days: usize) -> f64 {
+        let year = (day / Period::MARKET_DAYS_PER_YEAR as usize) as u64;
+        match self {
+            GlideSchedule::Linear { start, end } => {
+                let progress = day as f64 / days.saturating_sub(1).max(1) as f64;
+                start.amount() + (end.amount() - start.amount()) * f64::min(progress, 1.0)
+            }
+            GlideSchedule::Steps(steps) => {
+                let mut steps = steps.iter().flatten();
+                let first = steps.next().expect("A step schedule has at least one step");
+                steps
+                    .take_while(|step| step.year() <= year)
+                    .last()
+                    .unwrap_or(first)
+                    .leverage()
+                    .amount()
+            }
+            GlideSchedule::AgeBased {
+                starting_age,
+                base,
+                min_leverage,
+                max_leverage,
+            } => {
+                let age = starting_age + year;
+                let allocation = (*base as f64 - age as f64) / 100.0;
+                f64::clamp(allocation, min_leverage.amount(), max_leverage.amount())
+            }
+        }
+    }
+}
+
+impl Display for GlideSchedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GlideSchedule::Linear { start, end } => {
+                write!(f, "Glide {}x→{}x", start.amount(), end.amount())
+            }
+            GlideSchedule::Steps(steps) => {
+                let steps = steps
+                    .iter()
+                    .flatten()
+                    .map(|step| format!("{}x@{}y", step.leverage().amount(), step.year()))
+                    .collect::<Vec<_>>();
+                write!(f, "Steps {}", steps.join(" "))
+            }
+            GlideSchedule::AgeBased {
+                starting_age, base, ..
+            } => write!(f, "{} minus age from {}", base, starting_age),
+        }
+    }
+}
+
+/// A self-managed position whose target leverage follows a schedule over the path, e.g.
+/// lifecycle investing's 2x while young and less later on. The position is traded back to
+/// the day's target on `rebalance`, so a periodic schedule only picks up a change at its
+/// next rebalance while a band picks it up once the target has moved far enough.
+///
+/// The variant's leverage scales the schedule, so at 1x the schedule is held as given.
+/// Borrowing costs the path's base rates plus `loan_spread`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GlidePath {
+    schedule: GlideSchedule,
+    rebalance: RebalanceSchedule,
+    loan_spread: Percent,
+}
+
+impl GlidePath {
+    pub fn new(
+        schedule: GlideSchedule,
+        rebalance: RebalanceSchedule,
+        loan_spread: Percent,
+    ) -> Self {
+        Self {
+            schedule,
+            rebalance,
+            loan_spread,
+        }
+    }
+
+    pub fn schedule(&self) -> GlideSchedule {
+        self.schedule
+    }
+
+    pub(super) fn simulate(
+        &self,
+        leverage: Leverage,
+        expense_ratio: ExpenseRatio,
+        trading_costs: &TradingCosts,
+        market_history: &MarketHistory,
+    ) -> (PriceHistory, RealizedLeverage, CostDrag, Activity) {
+        let days = market_history.price_history().iter().len();
+        let target_on = |day| leverage.amount() * self.schedule.leverage_on(day, days);
+
+        Account::new(Leverage::new(target_on(0))).simulate(
+            expense_ratio,
+            self.loan_spread,
+            trading_costs,
+            market_history,
+            |day, account| {
+                // Trades at this close are held over the next day
+                let target = target_on(day + 1);
+                let actual = account.leverage();
+                let due = match target == 0.0 {
+                    true => actual != 0.0,
+                    false => self.rebalance.is_due(day, target, actual),
+                };
+                if due {
+                    account.set_position(target * account.equity());
+                }
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pricing::{ExpenseRatioSchedule, LeverageMode, PriceChange};
+
+    use super::*;
+
+    #[test]
+    fn test_glide_paths() {
+        let days_per_year = Period::MARKET_DAYS_PER_YEAR as usize;
+        let steps = GlideSchedule::steps(&[
+            GlideStep::new(0, Leverage::new(2.0)),
+            GlideStep::new(2, Leverage::new(1.0)),
+        ]);
+        assert_eq!(steps.leverage_on(0, 3 * days_per_year), 2.0);
+        assert_eq!(
+            steps.leverage_on(2 * days_per_year - 1, 3 * days_per_year),
+            2.0
+        );
+        assert_eq!(steps.leverage_on(2 * days_per_year, 3 * days_per_year), 1.0);
+
+        let linear = GlideSchedule::Linear {
+            start: Leverage::new(2.0),
+            end: Leverage::new(1.0),
+        };
+        assert_eq!(linear.leverage_on(0, 11), 2.0);
+        assert!((linear.leverage_on(5, 11) - 1.5).abs() < 1e-12);
+        assert_eq!(linear.leverage_on(10, 11), 1.0);
+
+        // 120 minus 30 is 90% in stocks, and at 10 that would be 110%
+        let age_based = |starting_age| GlideSchedule::AgeBased {
+            starting_age,
+            base: 120,
+            min_leverage: Leverage::new(0.0),
+            max_leverage: Leverage::new(1.0),
+        };
+        assert!((age_based(30).leverage_on(days_per_year, days_per_year) - 0.89).abs() < 1e-12);
+        assert_eq!(age_based(10).leverage_on(0, days_per_year), 1.0);
+
+        // Rebalanced daily, 2x for a +10% day and then 1x for a -10% day
+        let market_history: MarketHistory = [10.0, -10.0]
+            .iter()
+            .map(|&change| PriceChange::from(Percent::from_percent(change)))
+            .collect::<PriceHistory>()
+            .into();
+        let glide_path = GlidePath::new(
+            linear,
+            RebalanceSchedule::Periodic(Period::Days(1)),
+            Percent::zero(),
+        );
+        let total = LeverageMode::GlidePath(glide_path)
+            .simulate(
+                Leverage::new(1.0),
+                &ExpenseRatioSchedule::flat(Percent::zero()),
+                &TradingCosts::None,
+                &market_history,
+            )
+            .0
+            .total()
+            .percent_change()
+            .as_decimal();
+        assert!((total - (1.2 * 0.9 - 1.0)).abs() < 1e-12);
+    }
+}
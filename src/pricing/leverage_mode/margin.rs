@@ -0,0 +1,213 @@
+use crate::{
+    number::Percent,
+    pricing::{ExpenseRatio, Leverage, MarketHistory, PriceHistory},
+};
+
+use super::{account::Account, Activity, CostDrag, RealizedLeverage, TradingCosts};
+
+/// What happens to a margin account whose equity falls below the maintenance margin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Liquidation {
+    /// Sell everything and hold the remaining equity as cash.
+    Full,
+    /// Sell just enough to bring equity back up to the target share of the position.
+    Partial { target_margin: Percent },
+}
+
+/// A brokerage margin account: the loan accrues interest at the path's base rates plus
+/// `loan_spread`, and the broker liquidates when equity drops below `maintenance_margin` of
+/// the position. Margin calls are only checked at the close of each day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MarginAccount {
+    loan_spread: Percent,
+    maintenance_margin: Percent,
+    liquidation: Liquidation,
+}
+
+impl MarginAccount {
+    pub fn new(
+        loan_spread: Percent,
+        maintenance_margin: Percent,
+        liquidation: Liquidation,
+    ) -> Self {
+        if let Liquidation::Partial { target_margin } = liquidation {
+            assert!(
+                target_margin > maintenance_margin,
+                "Target margin must be above the maintenance margin"
+            );
+        }
+
+        Self {
+            loan_spread,
+            maintenance_margin,
+            liquidation,
+        }
+    }
+
+    pub fn liquidation(&self) -> Liquidation {
+        self.liquidation
+    }
+
+    pub(super) fn simulate(
+        &self,
+        leverage: Leverage,
+        expense_ratio: ExpenseRatio,
+        trading_costs: &TradingCosts,
+        market_history: &MarketHistory,
+    ) -> (PriceHistory, RealizedLeverage, CostDrag, Activity) {
+        let maintenance_margin = self.maintenance_margin.as_decimal();
+
+        Account::new(leverage).simulate(
+            expense_ratio,
+            self.loan_spread,
+            trading_costs,
+            market_history,
+            |_day, account| {
+                let exposure = account.position().abs();
+                if account.is_borrowing() && account.equity() < maintenance_margin * exposure {
+                    let exposure = match self.liquidation {
+                        Liquidation::Full => 0.0,
+                        Liquidation::Partial { target_margin } => {
+                            account.equity() / target_margin.as_decimal()
+                        }
+                    };
+                    account.set_position(exposure.copysign(account.position()));
+                }
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pricing::{
+        ExpenseRatioSchedule, Financing, LeverageMode, Period, PriceChange, RateSeries,
+    };
+
+    use super::*;
+
+    fn market_history(daily_changes: &[f64]) -> MarketHistory {
+        daily_changes
+            .iter()
+            .map(|&change| PriceChange::from(Percent::from_percent(change)))
+            .collect::<PriceHistory>()
+            .into()
+    }
+
+    #[test]
+    fn test_margin_calls() {
+        let market_history = market_history(&[-20.0, 50.0]);
+        let leverage = Leverage::new(3.0);
+        let total = |liquidation| {
+            let account =
+                MarginAccount::new(Percent::zero(), Percent::from_percent(25.0), liquidation);
+            LeverageMode::Margin(account)
+                .simulate(
+                    leverage,
+                    &ExpenseRatioSchedule::flat(Percent::zero()),
+                    &TradingCosts::None,
+                    &market_history,
+                )
+                .0
+                .total()
+                .percent_change()
+                .as_percent()
+        };
+
+        // Position falls to 2.4 against a loan of 2, leaving equity of 0.4 (a 16.7% margin).
+        // Fully liquidated, the account sits in cash while the market recovers.
+        assert!((total(Liquidation::Full) - -60.0).abs() < 1e-9);
+        // Brought back to a 40% margin, the account holds 1.0 on a loan of 0.6 and recovers
+        // to equity of 0.9.
+        let partial = total(Liquidation::Partial {
+            target_margin: Percent::from_percent(40.0),
+        });
+        assert!((partial - -10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_margin_drifts_without_reset() {
+        // Equity is whatever the drifting position is worth less the fixed loan
+        let market_history = market_history(&[10.0, -10.0]);
+        let account = MarginAccount::new(
+            Percent::zero(),
+            Percent::from_percent(25.0),
+            Liquidation::Full,
+        );
+        let total = LeverageMode::Margin(account)
+            .simulate(
+                Leverage::new(2.0),
+                &ExpenseRatioSchedule::flat(Percent::zero()),
+                &TradingCosts::None,
+                &market_history,
+            )
+            .0
+            .total()
+            .percent_change()
+            .as_decimal();
+
+        let expected = 2.0 * 1.1 * 0.9 - 1.0 - 1.0;
+        assert!((total - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_loan_pays_base_rate_and_spread() {
+        // A fund's swap spread isn't charged on a broker loan, only the loan's own spread
+        let period = Period::Days(Period::MARKET_DAYS_PER_YEAR);
+        let days = period.as_days() as usize;
+        let financing = Financing::new(
+            RateSeries::Constant(Percent::from_percent(3.0)),
+            Percent::from_percent(1.0),
+        );
+        let market_history = MarketHistory::new(
+            vec![PriceChange::zero(); days].into_iter().collect(),
+            financing.daily_borrowing_rates(0, period),
+        )
+        .with_base_rates(financing.daily_base_rates(0, period));
+        let account = MarginAccount::new(
+            Percent::from_percent(2.0),
+            Percent::from_percent(25.0),
+            Liquidation::Full,
+        );
+        let total = LeverageMode::Margin(account)
+            .simulate(
+                Leverage::new(2.0),
+                &ExpenseRatioSchedule::flat(Percent::zero()),
+                &TradingCosts::None,
+                &market_history,
+            )
+            .0
+            .total()
+            .percent_change()
+            .as_decimal();
+
+        let daily_rate = |annual: f64| f64::powf(1.0 + annual, 1.0 / days as f64) - 1.0;
+        let loan = f64::powi(1.0 + daily_rate(0.03) + daily_rate(0.02), days as i32);
+        assert!((total - (2.0 - loan - 1.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_short_margin_call() {
+        // A short of 1 against cash of 2 rises to 1.82 after two up days, leaving equity of
+        // 0.18, so it's bought back before the market falls
+        let market_history = market_history(&[40.0, 30.0, -50.0]);
+        let account = MarginAccount::new(
+            Percent::zero(),
+            Percent::from_percent(25.0),
+            Liquidation::Full,
+        );
+        let total = LeverageMode::Margin(account)
+            .simulate(
+                Leverage::new(-1.0),
+                &ExpenseRatioSchedule::flat(Percent::zero()),
+                &TradingCosts::None,
+                &market_history,
+            )
+            .0
+            .total()
+            .percent_change()
+            .as_decimal();
+
+        assert!((total - (0.18 - 1.0)).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,24 @@
+mod account;
+mod activity;
+mod glide_path;
+mod margin;
+mod mode;
+mod portfolio;
+mod realized_leverage;
+mod rebalanced;
+mod rotation;
+mod signal;
+mod trading_costs;
+mod volatility_target;
+
+pub use activity::Activity;
+pub use glide_path::{GlidePath, GlideSchedule, GlideStep};
+pub use margin::{Liquidation, MarginAccount};
+pub use mode::LeverageMode;
+pub use portfolio::{Holding, Portfolio};
+pub use realized_leverage::RealizedLeverage;
+pub use rebalanced::{RebalanceSchedule, Rebalancing};
+pub use rotation::Rotation;
+pub use signal::Signal;
+pub use trading_costs::{CostDrag, PathTradingCosts, TradingCosts};
+pub use volatility_target::VolatilityTarget;
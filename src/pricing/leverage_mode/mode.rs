@@ -0,0 +1,187 @@
+use std::fmt::Display;
+
+use crate::{
+    number::Percent,
+    pricing::{ExpenseRatio, ExpenseRatioSchedule, Leverage, MarketHistory, PriceHistory},
+};
+
+use super::{
+    Activity, CostDrag, GlidePath, Liquidation, MarginAccount, Portfolio, RealizedLeverage,
+    Rebalancing, Rotation, TradingCosts, VolatilityTarget,
+};
+
+/// How a leveraged position is held over the life of a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LeverageMode {
+    /// A fund that resets to its target leverage at the end of every day.
+    DailyReset,
+    /// A margin loan taken out at the start and left to drift with the market.
+    Margin(MarginAccount),
+    /// A self-managed position brought back to its target leverage now and then.
+    Rebalanced(Rebalancing),
+    /// A self-managed position re-levered daily against trailing realized volatility.
+    VolatilityTarget(VolatilityTarget),
+    /// Daily reset funds switched between on a rule read from the underlying's path.
+    Rotation(Rotation),
+    /// Daily reset funds on several assets held at target weights, with every fund's
+    /// leverage scaled by the variant's.
+    Portfolio(Portfolio),
+    /// A self-managed position whose target leverage follows a schedule over the path.
+    GlidePath(GlidePath),
+}
+
+impl LeverageMode {
+    /// The expense ratio paid on the position. Self-managed positions lever up an
+    /// unleveraged fund, so they pay that fund's fees rather than a leveraged fund's. A
+    /// rotation pays this while risk on.
+    pub fn expense_ratio(
+        &self,
+        leverage: Leverage,
+        schedule: &ExpenseRatioSchedule,
+    ) -> ExpenseRatio {
+        match self {
+            LeverageMode::DailyReset | LeverageMode::Rotation(_) => {
+                schedule.expense_ratio(leverage)
+            }
+            LeverageMode::Margin(_)
+            | LeverageMode::Rebalanced(_)
+            | LeverageMode::VolatilityTarget(_)
+            | LeverageMode::GlidePath(_) => schedule.expense_ratio(Leverage::new(1.0)),
+            LeverageMode::Portfolio(portfolio) => portfolio.expense_ratio(leverage, schedule),
+        }
+    }
+
+    /// The daily changes in equity of the position along with the leverage it actually
+    /// held along the way, what its trades cost, and what its holder sold and was paid.
+    pub fn simulate(
+        &self,
+        leverage: Leverage,
+        expense_ratios: &ExpenseRatioSchedule,
+        trading_costs: &TradingCosts,
+        market_history: &MarketHistory,
+    ) -> (PriceHistory, RealizedLeverage, CostDrag, Activity) {
+        let expense_ratio = self.expense_ratio(leverage, expense_ratios);
+        match self {
+            LeverageMode::DailyReset => {
+                let price_history = daily_reset(
+                    leverage,
+                    expense_ratio,
+                    market_history.price_history(),
+                    market_history.dividends(0),
+                    market_history.cash_rates(),
+                    market_history.borrowing_rates(),
+                    market_history.base_rates(),
+                );
+                // Holders of the fund never trade, they're only paid what it passes on
+                let mut activity = Activity::none();
+                let dividends = market_history.dividends(0);
+                price_history
+                    .iter()
+                    .enumerate()
+                    .for_each(|(day, price_change)| {
+                        let multiplier = price_change.percent_change().as_multiplier();
+                        if multiplier > 0.0 {
+                            let dividend = fund_dividend(leverage.amount(), dividends, day);
+                            activity.record_dividend(day, dividend / multiplier);
+                        }
+                    });
+                (
+                    price_history,
+                    RealizedLeverage::constant(leverage.amount()),
+                    CostDrag::none(),
+                    activity,
+                )
+            }
+            LeverageMode::Margin(account) => {
+                account.simulate(leverage, expense_ratio, trading_costs, market_history)
+            }
+            LeverageMode::Rebalanced(rebalancing) => {
+                rebalancing.simulate(leverage, expense_ratio, trading_costs, market_history)
+            }
+            LeverageMode::VolatilityTarget(target) => {
+                target.simulate(leverage, expense_ratio, trading_costs, market_history)
+            }
+            LeverageMode::Rotation(rotation) => {
+                let off_expense_ratio = expense_ratios.expense_ratio(rotation.off_leverage());
+                rotation.simulate(
+                    leverage,
+                    expense_ratio,
+                    off_expense_ratio,
+                    trading_costs,
+                    market_history,
+                )
+            }
+            LeverageMode::Portfolio(portfolio) => {
+                portfolio.simulate(leverage, expense_ratios, trading_costs, market_history)
+            }
+            LeverageMode::GlidePath(glide_path) => {
+                glide_path.simulate(leverage, expense_ratio, trading_costs, market_history)
+            }
+        }
+    }
+}
+
+/// The daily changes of a fund that resets to `leverage` on an asset at every close,
+/// along with whatever dividends it passes on and interest on what it holds in cash.
+pub(super) fn daily_reset(
+    leverage: Leverage,
+    expense_ratio: ExpenseRatio,
+    asset: &PriceHistory,
+    dividends: Option<&PriceHistory>,
+    cash_rates: Option<&[Percent]>,
+    borrowing_rates: &[Percent],
+    base_rates: &[Percent],
+) -> PriceHistory {
+    let price_history = asset
+        .clone()
+        .apply_modifier(PriceHistory::leverage_modifier(leverage));
+    let price_history = match dividends {
+        Some(dividends) => {
+            price_history.apply_modifier(PriceHistory::dividend_modifier(leverage, dividends))
+        }
+        None => price_history,
+    };
+    let price_history = match cash_rates {
+        Some(cash_rates) => {
+            price_history.apply_modifier(PriceHistory::cash_modifier(leverage, cash_rates))
+        }
+        None => price_history,
+    };
+    price_history
+        .apply_modifier(PriceHistory::financing_modifier(
+            leverage,
+            borrowing_rates,
+            base_rates,
+        ))
+        .apply_modifier(PriceHistory::expense_ratio_modifier(expense_ratio))
+}
+
+/// The dividend a daily reset fund at `leverage` passes on for a day, as a share of its
+/// value at the previous close.
+pub(super) fn fund_dividend(leverage: f64, dividends: Option<&PriceHistory>, day: usize) -> f64 {
+    dividends.map_or(0.0, |dividends| {
+        f64::clamp(leverage, 0.0, 1.0) * dividends[day].percent_change().as_decimal()
+    })
+}
+
+impl Display for LeverageMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            LeverageMode::DailyReset => String::from("Daily reset"),
+            LeverageMode::Margin(margin) => match margin.liquidation() {
+                Liquidation::Full => String::from("Margin, sell all"),
+                Liquidation::Partial { target_margin } => {
+                    format!("Margin to {:.0}", target_margin)
+                }
+            },
+            LeverageMode::Rebalanced(rebalancing) => format!("{}", rebalancing.schedule()),
+            LeverageMode::VolatilityTarget(target) => {
+                format!("Vol {:.0}/x", target.target_volatility())
+            }
+            LeverageMode::Rotation(rotation) => format!("{}", rotation.signal()),
+            LeverageMode::Portfolio(portfolio) => format!("{}", portfolio),
+            LeverageMode::GlidePath(glide_path) => format!("{}", glide_path.schedule()),
+        };
+        f.pad(&name)
+    }
+}
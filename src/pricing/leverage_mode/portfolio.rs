@@ -0,0 +1,288 @@
+use std::fmt::Display;
+
+use crate::{
+    number::Percent,
+    pricing::{
+        ExpenseRatio, ExpenseRatioSchedule, Leverage, MarketHistory, Period, PriceChange,
+        PriceHistory,
+    },
+};
+
+use super::{
+    mode::{daily_reset, fund_dividend},
+    Activity, CostDrag, RealizedLeverage, RebalanceSchedule, TradingCosts,
+};
+
+const MAX_HOLDINGS: usize = 4;
+
+/// A daily reset fund on one of the simulated assets, held at a target weight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Holding {
+    asset: usize,
+    leverage: Leverage,
+    weight: Percent,
+}
+
+impl Holding {
+    /// `asset` indexes the assets of the market history, where 0 is the index.
+    pub fn new(asset: usize, leverage: Leverage, weight: Percent) -> Self {
+        if weight <= Percent::zero() {
+            panic!("Holding weights must be positive, got {}", weight);
+        }
+
+        Self {
+            asset,
+            leverage,
+            weight,
+        }
+    }
+
+    pub fn asset(&self) -> usize {
+        self.asset
+    }
+
+    pub fn leverage(&self) -> Leverage {
+        self.leverage
+    }
+
+    pub fn weight(&self) -> Percent {
+        self.weight
+    }
+}
+
+/// Several holdings, e.g. 55% in 3x stocks and 45% in 3x bonds, brought back to their
+/// target weights on a schedule. Between rebalances the weights drift with each fund.
+///
+/// The variant's leverage scales every fund's leverage, so at 1x each holding is held as
+/// given. Each fund pays the expense ratio for its own leverage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Portfolio {
+    // Fixed capacity so the portfolio can live in a descriptor
+    holdings: [Option<Holding>; MAX_HOLDINGS],
+    schedule: RebalanceSchedule,
+}
+
+impl Portfolio {
+    pub fn new(holdings: &[Holding], schedule: RebalanceSchedule) -> Self {
+        if holdings.is_empty() || holdings.len() > MAX_HOLDINGS {
+            panic!(
+                "A portfolio holds 1 to {} funds, got {}",
+                MAX_HOLDINGS,
+                holdings.len()
+            );
+        }
+        let total_weight = holdings
+            .iter()
+            .map(|holding| holding.weight().as_decimal())
+            .sum::<f64>();
+        if (total_weight - 1.0).abs() > 1e-9 {
+            panic!(
+                "Portfolio weights must add up to 100%, got {}",
+                total_weight
+            );
+        }
+
+        let mut slots = [None; MAX_HOLDINGS];
+        holdings
+            .iter()
+            .zip(slots.iter_mut())
+            .for_each(|(&holding, slot)| *slot = Some(holding));
+        Self {
+            holdings: slots,
+            schedule,
+        }
+    }
+
+    pub fn holdings(&self) -> impl Iterator<Item = Holding> + '_ {
+        self.holdings.iter().flatten().copied()
+    }
+
+    /// The weighted average of what the funds pay.
+    pub(super) fn expense_ratio(
+        &self,
+        leverage: Leverage,
+        schedule: &ExpenseRatioSchedule,
+    ) -> ExpenseRatio {
+        let annual_amount = self
+            .holdings()
+            .map(|holding| {
+                let fund_leverage = Self::fund_leverage(leverage, holding);
+                holding.weight().as_decimal() * schedule.annual_amount(fund_leverage).as_decimal()
+            })
+            .sum::<f64>();
+        ExpenseRatio::new(Percent::from_decimal(annual_amount), Period::Years(1))
+    }
+
+    fn fund_leverage(leverage: Leverage, holding: Holding) -> Leverage {
+        Leverage::new(leverage.amount() * holding.leverage().amount())
+    }
+
+    pub(super) fn simulate(
+        &self,
+        leverage: Leverage,
+        expense_ratios: &ExpenseRatioSchedule,
+        trading_costs: &TradingCosts,
+        market_history: &MarketHistory,
+    ) -> (PriceHistory, RealizedLeverage, CostDrag, Activity) {
+        let funds = self
+            .holdings()
+            .map(|holding| {
+                let fund_leverage = Self::fund_leverage(leverage, holding);
+                daily_reset(
+                    fund_leverage,
+                    expense_ratios.expense_ratio(fund_leverage),
+                    market_history.asset(holding.asset()),
+                    market_history.dividends(holding.asset()),
+                    market_history.cash_rates(),
+                    market_history.borrowing_rates(),
+                    market_history.base_rates(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let weights = self
+            .holdings()
+            .map(|holding| holding.weight().as_decimal())
+            .collect::<Vec<_>>();
+        let fund_leverages = self
+            .holdings()
+            .map(|holding| Self::fund_leverage(leverage, holding).amount())
+            .collect::<Vec<_>>();
+
+        let mut values = weights.clone();
+        let mut realized_leverage = RealizedLeverage::none();
+        let trading_costs = trading_costs.along(market_history.price_history());
+        let mut cost_drag = CostDrag::none();
+        let mut activity = Activity::none();
+        let dividends = self
+            .holdings()
+            .map(|holding| market_history.dividends(holding.asset()))
+            .collect::<Vec<_>>();
+        let days = market_history.price_history().iter().len();
+        let price_history = (0..days)
+            .map(|day| {
+                let start = values.iter().sum::<f64>();
+                if start <= 0.0 {
+                    return PriceChange::zero();
+                }
+                let exposure = values
+                    .iter()
+                    .zip(&fund_leverages)
+                    .map(|(value, fund_leverage)| value * fund_leverage)
+                    .sum::<f64>();
+                realized_leverage.record(exposure / start);
+                let paid = values
+                    .iter()
+                    .zip(&fund_leverages)
+                    .zip(&dividends)
+                    .map(|((value, &fund_leverage), &dividends)| {
+                        value * fund_dividend(fund_leverage, dividends, day)
+                    })
+                    .sum::<f64>();
+
+                values.iter_mut().zip(&funds).for_each(|(value, fund)| {
+                    let multiplier = fund[day].percent_change().as_multiplier();
+                    *value = f64::max(*value * multiplier, 0.0);
+                });
+                let mut end = values.iter().sum::<f64>();
+                if end > 0.0 {
+                    activity.record_dividend(day, paid / end);
+                }
+
+                let drifted = values.iter().zip(&weights).any(|(value, &weight)| {
+                    end > 0.0 && self.schedule.is_due(day, weight, value / end)
+                });
+                if drifted {
+                    let traded = values
+                        .iter()
+                        .zip(&weights)
+                        .map(|(value, weight)| (weight * end - value).abs())
+                        .sum::<f64>();
+                    // Whatever is over its weight is sold to buy what's under
+                    let sold = values
+                        .iter()
+                        .zip(&weights)
+                        .map(|(value, weight)| f64::max(value - weight * end, 0.0))
+                        .sum::<f64>();
+                    activity.record_sale(day, sold / end);
+                    let cost = f64::min(trading_costs.cost(day, traded), end);
+                    if cost > 0.0 {
+                        cost_drag.record(cost, end);
+                        end -= cost;
+                    }
+                    values
+                        .iter_mut()
+                        .zip(&weights)
+                        .for_each(|(value, weight)| *value = weight * end);
+                }
+
+                PriceChange::from(Percent::from_multiplier(end / start))
+            })
+            .collect();
+
+        (price_history, realized_leverage, cost_drag, activity)
+    }
+}
+
+impl Display for Portfolio {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let holdings = self
+            .holdings()
+            .map(|holding| {
+                format!(
+                    "{:.0} {}x#{}",
+                    holding.weight(),
+                    holding.leverage().amount(),
+                    holding.asset()
+                )
+            })
+            .collect::<Vec<_>>();
+        write!(f, "{} {}", holdings.join(" + "), self.schedule)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pricing::LeverageMode;
+
+    use super::*;
+
+    #[test]
+    fn test_portfolio_rebalancing() {
+        let history = |changes: [f64; 2]| {
+            changes
+                .iter()
+                .map(|&change| PriceChange::from(Percent::from_percent(change)))
+                .collect::<PriceHistory>()
+        };
+        let market_history = MarketHistory::with_assets(
+            vec![history([10.0, 10.0]), history([-10.0, -10.0])],
+            vec![Percent::zero(); 2],
+        );
+        let daily_changes = |schedule| {
+            let holdings = [
+                Holding::new(0, Leverage::new(1.0), Percent::from_percent(50.0)),
+                Holding::new(1, Leverage::new(1.0), Percent::from_percent(50.0)),
+            ];
+            LeverageMode::Portfolio(Portfolio::new(&holdings, schedule))
+                .simulate(
+                    Leverage::new(1.0),
+                    &ExpenseRatioSchedule::flat(Percent::zero()),
+                    &TradingCosts::None,
+                    &market_history,
+                )
+                .0
+                .into_iter()
+                .map(|price_change| price_change.percent_change().as_percent())
+                .collect::<Vec<_>>()
+        };
+
+        // Rebalanced daily the two assets keep cancelling out
+        let rebalanced = daily_changes(RebalanceSchedule::Periodic(Period::Days(1)));
+        assert!(rebalanced.iter().all(|change| change.abs() < 1e-9));
+
+        // Left to drift, the winner is 55% of the portfolio going into the second day
+        let drifting = daily_changes(RebalanceSchedule::quarterly());
+        assert!(drifting[0].abs() < 1e-9);
+        assert!((drifting[1] - 1.0).abs() < 1e-9);
+    }
+}
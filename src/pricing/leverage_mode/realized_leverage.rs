@@ -0,0 +1,58 @@
+/// Summary of the leverage a position actually held over the days of a single path.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RealizedLeverage {
+    sum: f64,
+    days: usize,
+    min: f64,
+    max: f64,
+}
+
+impl RealizedLeverage {
+    pub fn constant(leverage: f64) -> Self {
+        Self {
+            sum: leverage,
+            days: 1,
+            min: leverage,
+            max: leverage,
+        }
+    }
+
+    /// An empty summary, to `record` each day's leverage into.
+    pub fn none() -> Self {
+        Self {
+            sum: 0.0,
+            days: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    pub fn record(&mut self, leverage: f64) {
+        self.sum += leverage;
+        self.days += 1;
+        self.min = self.min.min(leverage);
+        self.max = self.max.max(leverage);
+    }
+
+    /// A path with no days recorded held nothing.
+    pub fn average(&self) -> f64 {
+        match self.days {
+            0 => 0.0,
+            days => self.sum / days as f64,
+        }
+    }
+
+    pub fn min(&self) -> f64 {
+        match self.days {
+            0 => 0.0,
+            _ => self.min,
+        }
+    }
+
+    pub fn max(&self) -> f64 {
+        match self.days {
+            0 => 0.0,
+            _ => self.max,
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use std::fmt::Display;
+
+use crate::{
+    number::Percent,
+    pricing::{ExpenseRatio, Leverage, MarketHistory, Period, PriceHistory},
+};
+
+use super::{account::Account, Activity, CostDrag, RealizedLeverage, TradingCosts};
+
+/// When a rebalanced position is brought back to its target leverage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RebalanceSchedule {
+    /// At the close of every period, e.g. `Period::Days(21)` for roughly monthly.
+    Periodic(Period),
+    /// Whenever actual leverage drifts more than `tolerance` (relative) from the target.
+    Band { tolerance: Percent },
+}
+
+impl RebalanceSchedule {
+    pub fn monthly() -> Self {
+        RebalanceSchedule::Periodic(Period::Days(21))
+    }
+
+    pub fn quarterly() -> Self {
+        RebalanceSchedule::Periodic(Period::Days(63))
+    }
+
+    pub(super) fn is_due(&self, day: usize, target: f64, actual: f64) -> bool {
+        match self {
+            RebalanceSchedule::Periodic(period) => (day as u64 + 1) % period.as_days() == 0,
+            RebalanceSchedule::Band { tolerance } => {
+                f64::abs(actual / target - 1.0) > tolerance.as_decimal()
+            }
+        }
+    }
+}
+
+impl Display for RebalanceSchedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RebalanceSchedule::Periodic(period) => write!(f, "Every {}d", period.as_days()),
+            RebalanceSchedule::Band { tolerance } => write!(f, "Band ±{:.0}", tolerance),
+        }
+    }
+}
+
+/// A self-managed position that borrows at the path's base rates plus `loan_spread`
+/// and trades back to its target leverage on a schedule. Between rebalances leverage
+/// drifts with the market, and there are no margin calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Rebalancing {
+    schedule: RebalanceSchedule,
+    loan_spread: Percent,
+}
+
+impl Rebalancing {
+    pub fn new(schedule: RebalanceSchedule, loan_spread: Percent) -> Self {
+        Self {
+            schedule,
+            loan_spread,
+        }
+    }
+
+    pub fn schedule(&self) -> RebalanceSchedule {
+        self.schedule
+    }
+
+    pub(super) fn simulate(
+        &self,
+        leverage: Leverage,
+        expense_ratio: ExpenseRatio,
+        trading_costs: &TradingCosts,
+        market_history: &MarketHistory,
+    ) -> (PriceHistory, RealizedLeverage, CostDrag, Activity) {
+        let target = leverage.amount();
+
+        Account::new(leverage).simulate(
+            expense_ratio,
+            self.loan_spread,
+            trading_costs,
+            market_history,
+            |day, account| {
+                let actual = account.leverage();
+                if target != 0.0 && self.schedule.is_due(day, target, actual) {
+                    account.set_position(target * account.equity());
+                }
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pricing::{ExpenseRatioSchedule, LeverageMode, PriceChange};
+
+    use super::*;
+
+    #[test]
+    fn test_rebalance_schedules() {
+        let market_history: MarketHistory = [10.0, -10.0, 10.0, -10.0]
+            .iter()
+            .map(|&change| PriceChange::from(Percent::from_percent(change)))
+            .collect::<PriceHistory>()
+            .into();
+        let total = |schedule| {
+            LeverageMode::Rebalanced(Rebalancing::new(schedule, Percent::zero()))
+                .simulate(
+                    Leverage::new(2.0),
+                    &ExpenseRatioSchedule::flat(Percent::zero()),
+                    &TradingCosts::None,
+                    &market_history,
+                )
+                .0
+                .total()
+                .percent_change()
+                .as_decimal()
+        };
+        let daily_reset = LeverageMode::DailyReset
+            .simulate(
+                Leverage::new(2.0),
+                &ExpenseRatioSchedule::flat(Percent::zero()),
+                &TradingCosts::None,
+                &market_history,
+            )
+            .0
+            .total()
+            .percent_change()
+            .as_decimal();
+
+        // Rebalancing every day is the daily reset fund without its fees
+        assert!((total(RebalanceSchedule::Periodic(Period::Days(1))) - daily_reset).abs() < 1e-12);
+
+        // Every other day: each pair of days leaves equity at 2 * 1.1 * 0.9 - 1
+        let every_other_day = total(RebalanceSchedule::Periodic(Period::Days(2)));
+        assert!((every_other_day - (f64::powi(0.98, 2) - 1.0)).abs() < 1e-12);
+
+        // A +10% day takes 2x to 2.2 / 1.2 = 1.83x, inside a 10% band but outside a 5% one
+        let wide_band = RebalanceSchedule::Band {
+            tolerance: Percent::from_percent(10.0),
+        };
+        let narrow_band = RebalanceSchedule::Band {
+            tolerance: Percent::from_percent(5.0),
+        };
+        assert!(
+            (total(wide_band) - total(RebalanceSchedule::Periodic(Period::Days(100)))).abs()
+                < 1e-12
+        );
+        assert!((total(narrow_band) - daily_reset).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{
+    number::Percent,
+    pricing::{
+        daily_financing_cost, ExpenseRatio, Leverage, MarketHistory, Period, PriceChange,
+        PriceHistory,
+    },
+};
+
+use super::{
+    mode::fund_dividend, signal::Signal, Activity, CostDrag, RealizedLeverage, TradingCosts,
+};
+
+/// Switches between daily reset funds on a signal: the variant's leverage while the signal
+/// is risk on, and `off_leverage` (e.g. 1x, or 0x for cash) while it's risk off.
+///
+/// A signal read at a close is acted on at the close `lag` later, so a lag of zero trades
+/// at the same close the signal is read. Each switch costs `switching_cost` of the change
+/// in leverage, covering the spread and commissions of trading out of one fund and into
+/// the other, on top of any trading costs. Until the signal has enough history the
+/// position is risk on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Rotation {
+    signal: Signal,
+    off_leverage: Leverage,
+    lag: Period,
+    switching_cost: Percent,
+}
+
+impl Rotation {
+    pub fn new(
+        signal: Signal,
+        off_leverage: Leverage,
+        lag: Period,
+        switching_cost: Percent,
+    ) -> Self {
+        Self {
+            signal,
+            off_leverage,
+            lag,
+            switching_cost,
+        }
+    }
+
+    pub fn signal(&self) -> Signal {
+        self.signal
+    }
+
+    pub fn off_leverage(&self) -> Leverage {
+        self.off_leverage
+    }
+
+    pub(super) fn simulate(
+        &self,
+        leverage: Leverage,
+        on_expense_ratio: ExpenseRatio,
+        off_expense_ratio: ExpenseRatio,
+        trading_costs: &TradingCosts,
+        market_history: &MarketHistory,
+    ) -> (PriceHistory, RealizedLeverage, CostDrag, Activity) {
+        let signals = self.signal.evaluate(market_history.price_history());
+        let lag = self.lag.as_days() as usize;
+        let borrowing_rates = market_history.borrowing_rates();
+        let base_rates = market_history.base_rates();
+        let dividends = market_history.dividends(0);
+        let cash_rates = market_history.cash_rates();
+        let mut realized_leverage = RealizedLeverage::none();
+        let trading_costs = trading_costs.along(market_history.price_history());
+        let mut cost_drag = CostDrag::none();
+        let mut activity = Activity::none();
+        let mut held = leverage.amount();
+        let mut equity = 1.0;
+
+        let price_history = market_history
+            .price_history()
+            .iter()
+            .enumerate()
+            .map(|(day, price_change)| {
+                // The position for this day was set at the previous close
+                let risk_on = day
+                    .checked_sub(lag + 1)
+                    .and_then(|signal_day| signals[signal_day])
+                    .unwrap_or(true);
+                let (target, expense_ratio) = match risk_on {
+                    true => (leverage.amount(), on_expense_ratio),
+                    false => (self.off_leverage.amount(), off_expense_ratio),
+                };
+                let switched = (target - held).abs();
+                let mut switching_cost = self.switching_cost.as_decimal() * switched;
+                // Switched at the previous close
+                let trading_cost = trading_costs.cost(day.saturating_sub(1), switched * equity);
+                if trading_cost > 0.0 && equity > 0.0 {
+                    cost_drag.record(trading_cost, equity);
+                    switching_cost = f64::min(switching_cost + trading_cost / equity, 1.0);
+                }
+                // Switching sells the whole of one fund to buy the other
+                if switched > 0.0 && held != 0.0 {
+                    activity.record_sale(day.saturating_sub(1), 1.0);
+                }
+                held = target;
+                realized_leverage.record(held);
+
+                let financing_cost =
+                    daily_financing_cost(held, borrowing_rates[day], base_rates[day]);
+                // Like any daily reset fund, only the unleveraged dividends are passed on
+                let dividend = fund_dividend(held, dividends, day);
+                let interest = cash_rates.map_or(0.0, |cash_rates| {
+                    f64::max(1.0 - held, 0.0) * cash_rates[day].as_decimal()
+                });
+                let change =
+                    held * price_change.percent_change().as_decimal() + dividend + interest
+                        - financing_cost;
+                let multiplier =
+                    (1.0 + change) * expense_ratio.multiplier() * (1.0 - switching_cost);
+                equity *= f64::max(multiplier, 0.0);
+                if multiplier > 0.0 {
+                    activity.record_dividend(day, dividend / multiplier);
+                }
+                PriceChange::from(Percent::from_decimal(multiplier - 1.0))
+            })
+            .collect();
+
+        (price_history, realized_leverage, cost_drag, activity)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pricing::{ExpenseRatioSchedule, LeverageMode};
+
+    use super::*;
+
+    #[test]
+    fn test_rotation_lag_and_costs() {
+        // A 20% drawdown signal trips at the close of the second day
+        let market_history: MarketHistory = [10.0, -30.0, 10.0, 10.0]
+            .iter()
+            .map(|&change| PriceChange::from(Percent::from_percent(change)))
+            .collect::<PriceHistory>()
+            .into();
+        let signal = Signal::Drawdown {
+            threshold: Percent::from_percent(20.0),
+        };
+        let daily_changes = |lag, switching_cost| {
+            let rotation = Rotation::new(
+                signal,
+                Leverage::new(0.0),
+                Period::Days(lag),
+                switching_cost,
+            );
+            LeverageMode::Rotation(rotation)
+                .simulate(
+                    Leverage::new(2.0),
+                    &ExpenseRatioSchedule::flat(Percent::zero()),
+                    &TradingCosts::None,
+                    &market_history,
+                )
+                .0
+                .into_iter()
+                .map(|price_change| price_change.percent_change().as_percent())
+                .collect::<Vec<_>>()
+        };
+        let assert_close = |actual: Vec<f64>, expected: &[f64]| {
+            for (actual, expected) in actual.iter().zip(expected) {
+                assert!(
+                    (actual - expected).abs() < 1e-9,
+                    "{:?} != {:?}",
+                    actual,
+                    expected
+                );
+            }
+        };
+
+        assert_close(daily_changes(0, Percent::zero()), &[20.0, -60.0, 0.0, 0.0]);
+        assert_close(daily_changes(1, Percent::zero()), &[20.0, -60.0, 20.0, 0.0]);
+        // Stepping down from 2x to cash trades two units of leverage
+        assert_close(
+            daily_changes(0, Percent::from_percent(0.5)),
+            &[20.0, -60.0, -1.0, 0.0],
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use std::{collections::VecDeque, fmt::Display};
+
+use crate::{
+    number::Percent,
+    pricing::{Period, PriceHistory},
+};
+
+/// A rule read from the underlying's price path at each close, deciding whether to hold
+/// the leveraged position (risk on) or step down (risk off).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Signal {
+    /// Risk on while the price is above its simple moving average over `days`.
+    MovingAverage { days: u64 },
+    /// Risk on while the price is less than `threshold` below its highest close.
+    Drawdown { threshold: Percent },
+    /// Risk on while annualized volatility over `lookback` is below `threshold`.
+    Volatility {
+        lookback: Period,
+        threshold: Percent,
+    },
+}
+
+impl Signal {
+    /// Whether the signal is risk on at each close of the path, or `None` while there isn't
+    /// enough history to tell.
+    pub fn evaluate(&self, price_history: &PriceHistory) -> Vec<Option<bool>> {
+        let changes = price_history
+            .iter()
+            .map(|price_change| price_change.percent_change());
+        let mut price = 1.0;
+        let prices: Vec<f64> = changes
+            .clone()
+            .map(|change| {
+                price *= change.as_multiplier();
+                price
+            })
+            .collect();
+
+        match *self {
+            Signal::MovingAverage { days } => {
+                let days = days as usize;
+                let mut sum = 0.0;
+                (0..prices.len())
+                    .map(|day| {
+                        sum += prices[day];
+                        if day >= days {
+                            sum -= prices[day - days];
+                        }
+                        (day + 1 >= days).then(|| prices[day] > sum / days as f64)
+                    })
+                    .collect()
+            }
+            Signal::Drawdown { threshold } => {
+                let floor = (-threshold).as_multiplier();
+                let mut peak = 1.0f64;
+                prices
+                    .iter()
+                    .map(|&price| {
+                        peak = peak.max(price);
+                        Some(price > peak * floor)
+                    })
+                    .collect()
+            }
+            Signal::Volatility {
+                lookback,
+                threshold,
+            } => {
+                let mut window = TrailingVolatility::new(lookback);
+                changes
+                    .map(|change| {
+                        window.push(change.as_decimal());
+                        window
+                            .annualized_volatility()
+                            .map(|volatility| volatility < threshold.as_decimal())
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+impl Display for Signal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Signal::MovingAverage { days } => write!(f, "SMA {}d", days),
+            Signal::Drawdown { threshold } => write!(f, "DD {:.0}", threshold),
+            Signal::Volatility { threshold, .. } => write!(f, "Vol < {:.0}", threshold),
+        }
+    }
+}
+
+/// Running sums over the trailing `lookback` daily changes, for the sample volatility of
+/// the window. There's no volatility until the whole lookback is in view.
+#[derive(Debug, Clone)]
+pub struct TrailingVolatility {
+    lookback: usize,
+    window: VecDeque<f64>,
+    sum: f64,
+    sum_of_squares: f64,
+}
+
+impl TrailingVolatility {
+    pub fn new(lookback: Period) -> Self {
+        let lookback = lookback.as_days() as usize;
+        Self {
+            lookback,
+            window: VecDeque::with_capacity(lookback + 1),
+            sum: 0.0,
+            sum_of_squares: 0.0,
+        }
+    }
+
+    /// Adds the latest change, dropping the one that falls out of the window.
+    pub fn push(&mut self, change: f64) {
+        self.window.push_back(change);
+        self.sum += change;
+        self.sum_of_squares += change * change;
+        if self.window.len() > self.lookback {
+            if let Some(removed) = self.window.pop_front() {
+                self.sum -= removed;
+                self.sum_of_squares -= removed * removed;
+            }
+        }
+    }
+
+    pub fn daily_volatility(&self) -> Option<f64> {
+        if self.window.len() < usize::max(self.lookback, 2) {
+            return None;
+        }
+
+        let count = self.window.len() as f64;
+        let variance = (self.sum_of_squares - self.sum * self.sum / count) / (count - 1.0);
+        Some(variance.max(0.0).sqrt())
+    }
+
+    pub fn annualized_volatility(&self) -> Option<f64> {
+        let days_per_year = Period::MARKET_DAYS_PER_YEAR as f64;
+        self.daily_volatility()
+            .map(|volatility| volatility * days_per_year.sqrt())
+    }
+}
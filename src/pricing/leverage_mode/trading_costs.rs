@@ -0,0 +1,152 @@
+use crate::{
+    number::Percent,
+    pricing::{Period, PriceHistory},
+};
+
+use super::signal::TrailingVolatility;
+
+/// What it costs to trade, charged whenever a self-managed position, rotation or portfolio
+/// trades. Daily reset funds trade inside the fund, which their expense ratio covers.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TradingCosts {
+    #[default]
+    None,
+    /// A flat commission on every trade, as a share of the starting equity.
+    PerTrade { cost: Percent },
+    /// A share of the notional traded, e.g. commissions in bps plus half the bid/ask spread.
+    Notional { rate: Percent },
+    /// Market impact of `multiple` times the underlying's daily volatility over the trailing
+    /// `lookback`, per unit of notional traded. Trades are free until the whole lookback is
+    /// in view.
+    VolatilityScaled { multiple: f64, lookback: Period },
+}
+
+impl TradingCosts {
+    /// The costs of trading on each day of a path of the underlying.
+    pub fn along(&self, underlying: &PriceHistory) -> PathTradingCosts {
+        let slippage_rates = match self {
+            TradingCosts::VolatilityScaled { multiple, lookback } => {
+                let mut window = TrailingVolatility::new(*lookback);
+                underlying
+                    .iter()
+                    .map(|price_change| {
+                        window.push(price_change.percent_change().as_decimal());
+                        multiple * window.daily_volatility().unwrap_or(0.0)
+                    })
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+
+        PathTradingCosts {
+            costs: *self,
+            slippage_rates,
+        }
+    }
+}
+
+/// Trading costs for the days of a single path.
+pub struct PathTradingCosts {
+    costs: TradingCosts,
+    slippage_rates: Vec<f64>,
+}
+
+impl PathTradingCosts {
+    /// What trading `notional`, in units of starting equity, costs at the close of `day`.
+    pub fn cost(&self, day: usize, notional: f64) -> f64 {
+        // Leaves out the dust of rebalancing to where the position already is
+        if notional < 1e-12 {
+            return 0.0;
+        }
+
+        match self.costs {
+            TradingCosts::None => 0.0,
+            TradingCosts::PerTrade { cost } => cost.as_decimal(),
+            TradingCosts::Notional { rate } => rate.as_decimal() * notional,
+            TradingCosts::VolatilityScaled { .. } => self.slippage_rates[day] * notional,
+        }
+    }
+}
+
+/// How much of a path's equity went to trading costs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CostDrag {
+    // Share of equity left after each cost, compounded
+    kept: f64,
+    trades: usize,
+}
+
+impl CostDrag {
+    pub fn none() -> Self {
+        Self {
+            kept: 1.0,
+            trades: 0,
+        }
+    }
+
+    /// Records a trade that cost `cost` out of `equity`, both in units of starting equity.
+    pub fn record(&mut self, cost: f64, equity: f64) {
+        if equity > 0.0 {
+            self.kept *= 1.0 - f64::min(cost / equity, 1.0);
+        }
+        self.trades += 1;
+    }
+
+    /// The share of the ending equity lost to trading costs.
+    pub fn drag(&self) -> Percent {
+        Percent::from_decimal(1.0 - self.kept)
+    }
+
+    pub fn trades(&self) -> usize {
+        self.trades
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pricing::{
+        ExpenseRatioSchedule, Leverage, LeverageMode, MarketHistory, PriceChange,
+        RebalanceSchedule, Rebalancing,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_rebalancing_costs() {
+        let market_history: MarketHistory = [10.0, -10.0]
+            .iter()
+            .map(|&change| PriceChange::from(Percent::from_percent(change)))
+            .collect::<PriceHistory>()
+            .into();
+        let simulate = |trading_costs| {
+            let rebalancing = Rebalancing::new(
+                RebalanceSchedule::Periodic(Period::Days(1)),
+                Percent::zero(),
+            );
+            let (price_history, _, cost_drag, _) = LeverageMode::Rebalanced(rebalancing).simulate(
+                Leverage::new(2.0),
+                &ExpenseRatioSchedule::flat(Percent::zero()),
+                &trading_costs,
+                &market_history,
+            );
+            (
+                price_history.total().percent_change().as_decimal(),
+                cost_drag,
+            )
+        };
+
+        // 2.2 of 1.2 equity goes back to 2.4 by buying 0.2, which costs 0.002. Then 2.16
+        // of 0.958 goes back to 1.916 by selling 0.244, which costs 0.00244.
+        let (total, cost_drag) = simulate(TradingCosts::Notional {
+            rate: Percent::from_percent(1.0),
+        });
+        assert_eq!(cost_drag.trades(), 2);
+        assert!((total - (0.958 - 0.00244 - 1.0)).abs() < 1e-12);
+        let expected_drag = 1.0 - (1.0 - 0.002 / 1.2) * (1.0 - 0.00244 / 0.958);
+        assert!((cost_drag.drag().as_decimal() - expected_drag).abs() < 1e-12);
+
+        let (free_total, free_drag) = simulate(TradingCosts::None);
+        assert!((free_total - (0.96 - 1.0)).abs() < 1e-12);
+        assert_eq!(free_drag.drag(), Percent::zero());
+    }
+}
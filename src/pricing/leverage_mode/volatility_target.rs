@@ -0,0 +1,153 @@
+use crate::{
+    number::Percent,
+    pricing::{ExpenseRatio, Leverage, MarketHistory, Period, PriceHistory},
+};
+
+use super::{
+    account::Account, signal::TrailingVolatility, Activity, CostDrag, RealizedLeverage,
+    TradingCosts,
+};
+
+/// A self-managed position that re-levers at every close, scaling its leverage by how the
+/// underlying's trailing realized volatility compares to a target.
+///
+/// The variant's leverage is what's held when realized volatility is exactly on target,
+/// so the position aims for that multiple of the target volatility (`scaled_target`), and
+/// holds just that leverage until a whole `lookback` of volatility is in view. The size of
+/// the resulting leverage is kept within `min_leverage` and `max_leverage`, and borrowing
+/// costs the path's base rates plus `loan_spread`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VolatilityTarget {
+    target_volatility: Percent,
+    lookback: Period,
+    min_leverage: Leverage,
+    max_leverage: Leverage,
+    loan_spread: Percent,
+}
+
+impl VolatilityTarget {
+    pub fn new(
+        target_volatility: Percent,
+        lookback: Period,
+        min_leverage: Leverage,
+        max_leverage: Leverage,
+        loan_spread: Percent,
+    ) -> Self {
+        if min_leverage.is_inverse() || max_leverage < min_leverage {
+            panic!(
+                "Invalid leverage caps: {} to {}",
+                min_leverage.amount(),
+                max_leverage.amount()
+            );
+        }
+
+        Self {
+            target_volatility,
+            lookback,
+            min_leverage,
+            max_leverage,
+            loan_spread,
+        }
+    }
+
+    pub fn target_volatility(&self) -> Percent {
+        self.target_volatility
+    }
+
+    /// The volatility a variant at `leverage` aims for.
+    pub fn scaled_target(&self, leverage: Leverage) -> Percent {
+        self.target_volatility * Percent::from_decimal(leverage.amount().abs())
+    }
+
+    /// The leverage to hold given the volatility realized so far, if there's enough of it.
+    fn leverage_for(&self, leverage: Leverage, realized_volatility: Option<f64>) -> f64 {
+        let scale = match realized_volatility {
+            Some(volatility) if volatility > 0.0 => {
+                self.target_volatility.as_decimal() / volatility
+            }
+            _ => 1.0,
+        };
+        let size = f64::clamp(
+            leverage.amount().abs() * scale,
+            self.min_leverage.amount(),
+            self.max_leverage.amount(),
+        );
+        size.copysign(leverage.amount())
+    }
+
+    pub(super) fn simulate(
+        &self,
+        leverage: Leverage,
+        expense_ratio: ExpenseRatio,
+        trading_costs: &TradingCosts,
+        market_history: &MarketHistory,
+    ) -> (PriceHistory, RealizedLeverage, CostDrag, Activity) {
+        let underlying = market_history.price_history();
+        let mut window = TrailingVolatility::new(self.lookback);
+
+        let initial_leverage = Leverage::new(self.leverage_for(leverage, None));
+        Account::new(initial_leverage).simulate(
+            expense_ratio,
+            self.loan_spread,
+            trading_costs,
+            market_history,
+            |day, account| {
+                window.push(underlying[day].percent_change().as_decimal());
+
+                let target = self.leverage_for(leverage, window.annualized_volatility());
+                account.set_position(target * account.equity());
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pricing::{ExpenseRatioSchedule, LeverageMode, PriceChange};
+
+    use super::*;
+
+    #[test]
+    fn test_volatility_scaling() {
+        // Every six days of alternating moves of ±1% have an annualized volatility of about
+        // 17%
+        let market_history: MarketHistory = (0..12)
+            .map(|day| if day % 2 == 0 { 1.0 } else { -1.0 })
+            .map(|change| PriceChange::from(Percent::from_percent(change)))
+            .collect::<PriceHistory>()
+            .into();
+        let volatility = 0.01 * f64::sqrt(6.0 / 5.0 * 253.0);
+        let strategy = |target| {
+            VolatilityTarget::new(
+                Percent::from_decimal(target),
+                Period::Days(6),
+                Leverage::new(0.0),
+                Leverage::new(3.0),
+                Percent::zero(),
+            )
+        };
+        let realized = |target| {
+            let (_, realized, _, _) = LeverageMode::VolatilityTarget(strategy(target)).simulate(
+                Leverage::new(2.0),
+                &ExpenseRatioSchedule::flat(Percent::zero()),
+                &TradingCosts::None,
+                &market_history,
+            );
+            realized
+        };
+
+        // Until the whole lookback is in view the variant's leverage is held as is, and
+        // once it is the path's volatility is right on target
+        let on_target = realized(volatility);
+        assert!((on_target.min() - 2.0).abs() < 1e-9);
+        assert!((on_target.max() - 2.0).abs() < 1e-9);
+
+        // Asking for half the volatility halves the leverage once it's measured
+        let half = realized(volatility / 2.0);
+        assert!((half.min() - 1.0).abs() < 1e-9);
+        assert!((half.max() - 2.0).abs() < 1e-9);
+
+        // Asking for far more volatility than the path has runs into the cap
+        assert!((realized(volatility * 10.0).max() - 3.0).abs() < 1e-9);
+    }
+}
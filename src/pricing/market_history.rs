@@ -0,0 +1,137 @@
+use crate::number::Percent;
+
+use super::{PriceChange, PriceHistory};
+
+/// Everything simulated for a single path: the daily price changes of the underlying
+/// index, and of any other assets a portfolio can hold, along with the rates in effect on
+/// each of those days and, optionally, the rates a broker's loans are priced off, what
+/// cash earns, the index's dividends and how much prices rose each day.
+#[derive(Debug, Clone)]
+pub struct MarketHistory {
+    // The index is always the first asset
+    assets: Vec<PriceHistory>,
+    borrowing_rates: Vec<Percent>,
+    base_rates: Option<Vec<Percent>>,
+    cash_rates: Option<Vec<Percent>>,
+    dividends: Option<PriceHistory>,
+    inflation: Option<PriceHistory>,
+}
+
+impl MarketHistory {
+    pub fn new(price_history: PriceHistory, borrowing_rates: Vec<Percent>) -> Self {
+        Self::with_assets(vec![price_history], borrowing_rates)
+    }
+
+    /// A path of several assets over the same days, starting with the index.
+    pub fn with_assets(assets: Vec<PriceHistory>, borrowing_rates: Vec<Percent>) -> Self {
+        debug_assert!(assets
+            .iter()
+            .all(|asset| borrowing_rates.len() >= asset.iter().len()));
+        Self {
+            assets,
+            borrowing_rates,
+            base_rates: None,
+            cash_rates: None,
+            dividends: None,
+            inflation: None,
+        }
+    }
+
+    /// The same path with broker loans priced off `base_rates`, the borrowing rates before
+    /// a fund's swap spread, rather than off the borrowing rates themselves.
+    pub fn with_base_rates(self, base_rates: Vec<Percent>) -> Self {
+        debug_assert!(base_rates.len() >= self.price_history().iter().len());
+        Self {
+            base_rates: Some(base_rates),
+            ..self
+        }
+    }
+
+    /// The same path with cash earning `cash_rates` each day, rather than nothing.
+    pub fn with_cash_rates(self, cash_rates: Vec<Percent>) -> Self {
+        debug_assert!(cash_rates.len() >= self.price_history().iter().len());
+        Self {
+            cash_rates: Some(cash_rates),
+            ..self
+        }
+    }
+
+    /// The same path with the index's dividend yield on each of its days.
+    pub fn with_dividends(self, dividends: PriceHistory) -> Self {
+        debug_assert!(dividends.iter().len() >= self.price_history().iter().len());
+        Self {
+            dividends: Some(dividends),
+            ..self
+        }
+    }
+
+    /// The same path with the daily change in consumer prices on each of its days.
+    pub fn with_inflation(self, inflation: PriceHistory) -> Self {
+        debug_assert!(inflation.iter().len() >= self.price_history().iter().len());
+        Self {
+            inflation: Some(inflation),
+            ..self
+        }
+    }
+
+    pub fn price_history(&self) -> &PriceHistory {
+        &self.assets[0]
+    }
+
+    /// The daily price changes of an asset, where asset 0 is the index.
+    pub fn asset(&self, asset: usize) -> &PriceHistory {
+        self.assets.get(asset).unwrap_or_else(|| {
+            panic!(
+                "Asset {} isn't simulated, only {} are",
+                asset,
+                self.assets.len()
+            )
+        })
+    }
+
+    /// The daily dividend yield of an asset, if it's paid separately from its price changes.
+    /// Only the index has dividends.
+    pub fn dividends(&self, asset: usize) -> Option<&PriceHistory> {
+        match asset {
+            0 => self.dividends.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Total return of the index over the path, with its dividends reinvested.
+    pub fn total_return(&self) -> PriceChange {
+        let price_return = self.price_history().total();
+        match &self.dividends {
+            Some(dividends) => price_return.compose(dividends.total()),
+            None => price_return,
+        }
+    }
+
+    /// Daily change in consumer prices, if inflation is simulated.
+    pub fn inflation(&self) -> Option<&PriceHistory> {
+        self.inflation.as_ref()
+    }
+
+    /// Daily interest on a unit of cash, if cash earns anything.
+    pub fn cash_rates(&self) -> Option<&[Percent]> {
+        self.cash_rates.as_deref()
+    }
+
+    /// Daily cost of borrowing a unit of the underlying.
+    pub fn borrowing_rates(&self) -> &[Percent] {
+        &self.borrowing_rates
+    }
+
+    /// Daily rate a broker's loan is priced off, before its own spread. Without separate
+    /// base rates it's the borrowing rate.
+    pub fn base_rates(&self) -> &[Percent] {
+        self.base_rates.as_deref().unwrap_or(&self.borrowing_rates)
+    }
+}
+
+impl From<PriceHistory> for MarketHistory {
+    fn from(price_history: PriceHistory) -> Self {
+        let days = price_history.iter().len();
+        Self::new(price_history, vec![Percent::zero(); days])
+    }
+}
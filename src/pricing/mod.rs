@@ -1,15 +1,44 @@
+mod cash_flows;
+mod dividends;
 mod expense_ratio;
+mod financing;
+mod inflation;
 mod leverage;
+mod leverage_grid;
+mod leverage_mode;
+mod market_history;
 mod period;
 mod price_change;
 mod price_history;
 mod price_history_variants;
 mod pricing_strategy;
+mod rate_series;
+mod retirement;
+mod ruin;
+mod stops;
+mod taxes;
 
-pub use expense_ratio::ExpenseRatio;
+pub use cash_flows::{CashFlowOutcome, CashFlows};
+pub use dividends::Dividends;
+pub use expense_ratio::{ExpenseRatio, ExpenseRatioSchedule, ScheduleInterpolation};
+pub use financing::{daily_financing_cost, Financing};
+pub use inflation::Inflation;
 pub use leverage::Leverage;
+pub use leverage_grid::LeverageGrid;
+pub use leverage_mode::{
+    Activity, CostDrag, GlidePath, GlideSchedule, GlideStep, Holding, LeverageMode, Liquidation,
+    MarginAccount, Portfolio, RealizedLeverage, RebalanceSchedule, Rebalancing, Rotation, Signal,
+    TradingCosts, VolatilityTarget,
+};
+pub use market_history::MarketHistory;
 pub use period::*;
 pub use price_change::PriceChange;
 pub use price_history::PriceHistory;
 pub use price_history_variants::*;
 pub use pricing_strategy::*;
+pub use rate_series::RateProcess;
+pub use rate_series::RateSeries;
+pub use retirement::{Retirement, RetirementOutcome, WithdrawalRule};
+pub use ruin::{Ruin, RuinCause, Termination};
+pub use stops::{Reentry, StopOutcome, StopRule, StopTrigger};
+pub use taxes::{TaxOutcome, TaxRates, TaxableAccount};
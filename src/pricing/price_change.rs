@@ -44,6 +44,14 @@ impl PriceChange {
         Percent::from_multiplier(total_multiplier).into()
     }
 
+    /// The real price change, taking out the purchasing power `inflation` cost over the
+    /// same time.
+    pub fn deflate(&self, inflation: PriceChange) -> PriceChange {
+        let real_multiplier =
+            self.percent_change().as_multiplier() / inflation.percent_change().as_multiplier();
+        Percent::from_multiplier(real_multiplier).into()
+    }
+
     pub fn compose(&self, other: PriceChange) -> PriceChange {
         self.percent_change.compose(other.percent_change).into()
     }
@@ -13,7 +13,7 @@ use fmt::Debug;
 
 use crate::number::Percent;
 
-use super::{ExpenseRatio, Leverage, PriceChange};
+use super::{daily_financing_cost, Activity, ExpenseRatio, Leverage, PriceChange};
 
 #[derive(Debug, Clone)]
 pub struct PriceHistory {
@@ -48,7 +48,7 @@ impl PriceHistory {
         if modifier.modifications_needed() {
             for i in 0..self.price_changes.len() {
                 let price_change = self.price_changes[i];
-                self.price_changes[i] = modifier.modify_price_change(price_change)
+                self.price_changes[i] = modifier.modify_price_change(i, price_change)
             }
         }
         self
@@ -58,10 +58,60 @@ impl PriceHistory {
         LeverageModifier { leverage }
     }
 
+    /// Charges the daily borrowing rates on the part of a leveraged position that is borrowed,
+    /// or the swap spread over the base rates on the whole of an inverse position.
+    pub fn financing_modifier<'a>(
+        leverage: Leverage,
+        borrowing_rates: &'a [Percent],
+        base_rates: &'a [Percent],
+    ) -> impl PriceHistoryModifier + 'a {
+        FinancingModifier {
+            leverage: leverage.amount(),
+            borrowing_rates,
+            base_rates,
+        }
+    }
+
+    /// Pays the daily cash rates on the part of a position that isn't invested. Inverse
+    /// positions hold both their equity and the proceeds of their short as cash.
+    pub fn cash_modifier(
+        leverage: Leverage,
+        cash_rates: &[Percent],
+    ) -> impl PriceHistoryModifier + '_ {
+        CashModifier {
+            cash: f64::max(1.0 - leverage.amount(), 0.0),
+            cash_rates,
+        }
+    }
+
+    /// Adds the dividends a daily reset fund passes on. Its swaps are on the price index, so
+    /// only what it holds outright pays out: never more than the unleveraged yield, and
+    /// nothing for inverse funds.
+    pub fn dividend_modifier(
+        leverage: Leverage,
+        dividends: &PriceHistory,
+    ) -> impl PriceHistoryModifier + '_ {
+        DividendModifier {
+            held: f64::clamp(leverage.amount(), 0.0, 1.0),
+            dividends,
+        }
+    }
+
+    /// Takes out the dividends a position was paid, as a share of its equity at each close,
+    /// as if each were withdrawn when paid rather than reinvested.
+    pub fn dividend_withdrawal_modifier(activity: &Activity) -> impl PriceHistoryModifier + '_ {
+        DividendWithdrawalModifier { activity }
+    }
+
     pub fn expense_ratio_modifier(expense_ratio: ExpenseRatio) -> impl PriceHistoryModifier {
         ExpenseRatioModifier { expense_ratio }
     }
 
+    /// Turns nominal daily changes into real ones, given each day's change in prices.
+    pub fn inflation_modifier(inflation: &PriceHistory) -> impl PriceHistoryModifier + '_ {
+        InflationModifier { inflation }
+    }
+
     pub fn total(&self) -> PriceChange {
         PriceChange::compose_all(&self.price_changes)
     }
@@ -125,7 +175,7 @@ impl From<&[PriceChange]> for PriceHistory {
 }
 
 pub trait PriceHistoryModifier: Debug {
-    fn modify_price_change(&self, price_change: PriceChange) -> PriceChange;
+    fn modify_price_change(&self, day: usize, price_change: PriceChange) -> PriceChange;
     fn modifications_needed(&self) -> bool;
 }
 
@@ -135,7 +185,7 @@ struct LeverageModifier {
 }
 
 impl PriceHistoryModifier for LeverageModifier {
-    fn modify_price_change(&self, price_change: PriceChange) -> PriceChange {
+    fn modify_price_change(&self, _day: usize, price_change: PriceChange) -> PriceChange {
         let updated_decimal_change =
             self.leverage.amount() * price_change.percent_change().as_decimal();
         Percent::from_decimal(updated_decimal_change).into()
@@ -145,13 +195,101 @@ impl PriceHistoryModifier for LeverageModifier {
         self.leverage != Leverage::new(1.0)
     }
 }
+
+#[derive(Debug, Clone)]
+struct FinancingModifier<'a> {
+    leverage: f64,
+    borrowing_rates: &'a [Percent],
+    base_rates: &'a [Percent],
+}
+
+impl PriceHistoryModifier for FinancingModifier<'_> {
+    fn modify_price_change(&self, day: usize, price_change: PriceChange) -> PriceChange {
+        let financing_cost = daily_financing_cost(
+            self.leverage,
+            self.borrowing_rates[day],
+            self.base_rates[day],
+        );
+        (price_change.percent_change() - Percent::from_decimal(financing_cost)).into()
+    }
+
+    fn modifications_needed(&self) -> bool {
+        self.leverage > 1.0 || self.leverage < 0.0
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CashModifier<'a> {
+    cash: f64,
+    cash_rates: &'a [Percent],
+}
+
+impl PriceHistoryModifier for CashModifier<'_> {
+    fn modify_price_change(&self, day: usize, price_change: PriceChange) -> PriceChange {
+        let interest = self.cash * self.cash_rates[day].as_decimal();
+        (price_change.percent_change() + Percent::from_decimal(interest)).into()
+    }
+
+    fn modifications_needed(&self) -> bool {
+        self.cash > 0.0
+    }
+}
+
+#[derive(Debug, Clone)]
+struct DividendModifier<'a> {
+    held: f64,
+    dividends: &'a PriceHistory,
+}
+
+impl PriceHistoryModifier for DividendModifier<'_> {
+    fn modify_price_change(&self, day: usize, price_change: PriceChange) -> PriceChange {
+        let dividend = self.held * self.dividends[day].percent_change().as_decimal();
+        (price_change.percent_change() + Percent::from_decimal(dividend)).into()
+    }
+
+    fn modifications_needed(&self) -> bool {
+        self.held > 0.0
+    }
+}
+
+#[derive(Debug, Clone)]
+struct DividendWithdrawalModifier<'a> {
+    activity: &'a Activity,
+}
+
+impl PriceHistoryModifier for DividendWithdrawalModifier<'_> {
+    fn modify_price_change(&self, day: usize, price_change: PriceChange) -> PriceChange {
+        let multiplier = price_change.percent_change().as_multiplier();
+        Percent::from_multiplier(multiplier * (1.0 - self.activity.dividend(day))).into()
+    }
+
+    fn modifications_needed(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone)]
+struct InflationModifier<'a> {
+    inflation: &'a PriceHistory,
+}
+
+impl PriceHistoryModifier for InflationModifier<'_> {
+    fn modify_price_change(&self, day: usize, price_change: PriceChange) -> PriceChange {
+        price_change.deflate(self.inflation[day])
+    }
+
+    fn modifications_needed(&self) -> bool {
+        true
+    }
+}
+
 #[derive(Debug, Clone)]
 struct ExpenseRatioModifier {
     expense_ratio: ExpenseRatio,
 }
 
 impl PriceHistoryModifier for ExpenseRatioModifier {
-    fn modify_price_change(&self, price_change: PriceChange) -> PriceChange {
+    fn modify_price_change(&self, _day: usize, price_change: PriceChange) -> PriceChange {
         price_change
             .percent_change()
             .compose(self.expense_ratio.amount())
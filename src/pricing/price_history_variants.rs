@@ -1,102 +1,438 @@
-use core::panic;
-
 use once_cell::sync::Lazy;
 
 use crate::number::Percent;
 
-use super::{ExpenseRatio, Leverage, Period, PriceChange, PriceHistory};
-
-static LEVERAGE_AMOUNTS: Lazy<Vec<Leverage>> = Lazy::new(|| {
-    let mut results = Vec::new();
-    let mut leverage = 0.0f64;
-    while leverage < 10.0 {
-        match leverage {
-            x if x >= 0.0 && x < 4.0 => {
-                leverage += 0.1;
-            }
-            x if x >= 4.0 && x < 5.0 => {
-                leverage += 0.5;
-            }
-            x if x >= 5.0 && x < 10.0 => {
-                leverage += 1.0;
-            }
-            _ => panic!("Unsupported leverage value: {}", leverage),
-        };
-        results.push(Leverage::new(leverage));
-    }
-
-    results
-});
+use super::{
+    ruin::track_ruin, Activity, CashFlowOutcome, CashFlows, CostDrag, ExpenseRatio,
+    ExpenseRatioSchedule, Leverage, LeverageGrid, LeverageMode, MarketHistory, Period, PriceChange,
+    PriceHistory, RealizedLeverage, Retirement, RetirementOutcome, Ruin, StopOutcome, StopRule,
+    TaxOutcome, TaxableAccount, Termination, TradingCosts,
+};
 
+#[allow(dead_code)]
 static PERIODS: Lazy<Vec<Period>> = Lazy::new(|| {
     vec![5, 10, 15, 20, 25, 30]
         .into_iter()
-        .map(|count| Period::Years(count))
+        .map(Period::Years)
         .collect()
 });
 
-pub fn leverage_amounts() -> &'static [Leverage] {
-    &LEVERAGE_AMOUNTS
-}
-
+#[allow(dead_code)]
 pub fn periods() -> &'static [Period] {
     &PERIODS
 }
 
 #[derive(Debug, Clone)]
 pub struct PriceHistoryVariants {
+    underlying_price_change: PriceChange,
+    underlying_total_return: PriceChange,
     total_price_changes: Vec<PriceChange>,
+    price_returns: Vec<PriceChange>,
+    real_price_changes: Vec<PriceChange>,
+    ruins: Vec<Option<Ruin>>,
+    realized_leverages: Vec<RealizedLeverage>,
+    cost_drags: Vec<CostDrag>,
+    cash_flow_outcomes: Vec<Option<CashFlowOutcome>>,
+    retirement_outcomes: Vec<Option<RetirementOutcome>>,
+    tax_outcomes: Vec<Option<TaxOutcome>>,
+    stop_outcomes: Vec<Option<StopOutcome>>,
     descriptors: Vec<PriceHistoryDescriptor>,
 }
 
+/// Everything recorded about one variant of a single path.
+#[derive(Debug, Clone, Copy)]
+pub struct VariantOutcome {
+    price_change: PriceChange,
+    price_return: PriceChange,
+    real_price_change: PriceChange,
+    underlying_price_change: PriceChange,
+    ruin: Option<Ruin>,
+    realized_leverage: RealizedLeverage,
+    cost_drag: CostDrag,
+    cash_flows: Option<CashFlowOutcome>,
+    retirement: Option<RetirementOutcome>,
+    taxes: Option<TaxOutcome>,
+    stops: Option<StopOutcome>,
+}
+
+impl VariantOutcome {
+    /// Total price change, after taxes on a lump sum if the variant is held in a taxable
+    /// account. Its price return and balances are always before taxes.
+    pub fn price_change(&self) -> PriceChange {
+        self.price_change
+    }
+
+    /// Total price change had the variant's dividends been withdrawn as they were paid
+    /// rather than reinvested, the same as the total one if dividends aren't simulated
+    /// separately.
+    pub fn price_return(&self) -> PriceChange {
+        self.price_return
+    }
+
+    /// Total price change after inflation, the same as the nominal one if inflation isn't
+    /// simulated.
+    pub fn real_price_change(&self) -> PriceChange {
+        self.real_price_change
+    }
+
+    /// Total price change of the unleveraged path with no expenses, leaving out dividends
+    /// paid separately.
+    pub fn underlying_price_change(&self) -> PriceChange {
+        self.underlying_price_change
+    }
+
+    pub fn ruin(&self) -> Option<Ruin> {
+        self.ruin
+    }
+
+    pub fn realized_leverage(&self) -> RealizedLeverage {
+        self.realized_leverage
+    }
+
+    /// How much of the variant's equity went to trading costs.
+    pub fn cost_drag(&self) -> CostDrag {
+        self.cost_drag
+    }
+
+    /// Where the balance ended up, if cash flows were simulated. In real terms when
+    /// inflation is simulated.
+    pub fn cash_flows(&self) -> Option<CashFlowOutcome> {
+        self.cash_flows
+    }
+
+    /// How retirement went, if it was simulated. In real terms when inflation is simulated,
+    /// so withdrawals keep their purchasing power.
+    pub fn retirement(&self) -> Option<RetirementOutcome> {
+        self.retirement
+    }
+
+    /// How the variant fared in a taxable account, if it's held in one. In real terms
+    /// when inflation is simulated.
+    pub fn taxes(&self) -> Option<TaxOutcome> {
+        self.taxes
+    }
+
+    /// What the stop did, if the variant has one. Everything else about the variant is
+    /// with the stop in place.
+    pub fn stops(&self) -> Option<StopOutcome> {
+        self.stops
+    }
+}
+
+/// What's followed along each variant's path once it's simulated, beyond its total.
+#[derive(Debug, Clone, Default)]
+pub struct OutcomeTracking {
+    terminations: Vec<Termination>,
+    cash_flows: Option<CashFlows>,
+    retirement: Option<Retirement>,
+    taxes: Option<TaxableAccount>,
+    stops: Vec<StopRule>,
+}
+
+impl OutcomeTracking {
+    pub fn new(
+        terminations: Vec<Termination>,
+        cash_flows: Option<CashFlows>,
+        retirement: Option<Retirement>,
+        taxes: Option<TaxableAccount>,
+        stops: Vec<StopRule>,
+    ) -> Self {
+        Self {
+            terminations,
+            cash_flows,
+            retirement,
+            taxes,
+            stops,
+        }
+    }
+
+    pub fn with_retirement(self, retirement: Retirement) -> Self {
+        Self {
+            retirement: Some(retirement),
+            ..self
+        }
+    }
+
+    /// A path is terminated on the first day any of these is triggered.
+    pub fn terminations(&self) -> &[Termination] {
+        &self.terminations
+    }
+
+    pub fn cash_flows(&self) -> Option<CashFlows> {
+        self.cash_flows
+    }
+
+    pub fn retirement(&self) -> Option<Retirement> {
+        self.retirement
+    }
+
+    /// Every variant is also held in this account, and its taxes are charged on the cash
+    /// flows if there are any, or on a single lump sum of 1.
+    pub fn taxes(&self) -> Option<TaxableAccount> {
+        self.taxes
+    }
+
+    /// Each is laid over every variant's path, next to the same variant without a stop.
+    pub fn stops(&self) -> &[StopRule] {
+        &self.stops
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct PriceHistoryDescriptor {
     leverage: Leverage,
+    mode: LeverageMode,
+    expense_ratio: ExpenseRatio,
     period: Period,
+    stop: Option<StopRule>,
+    taxes: Option<TaxableAccount>,
 }
 
 impl PriceHistoryDescriptor {
+    pub fn new(
+        leverage: Leverage,
+        mode: LeverageMode,
+        expense_ratio: ExpenseRatio,
+        period: Period,
+    ) -> Self {
+        Self {
+            leverage,
+            mode,
+            expense_ratio,
+            period,
+            stop: None,
+            taxes: None,
+        }
+    }
+
+    /// Lays a stop over the variant's path before anything else is followed along it.
+    pub fn with_stop(mut self, stop: StopRule) -> Self {
+        self.stop = Some(stop);
+        self
+    }
+
+    /// Holds the variant in a taxable account, so its price change is what's left once
+    /// the account is sold off and taxed.
+    pub fn with_taxes(mut self, taxes: TaxableAccount) -> Self {
+        self.taxes = Some(taxes);
+        self
+    }
+
     pub fn leverage(&self) -> Leverage {
         self.leverage
     }
 
+    pub fn mode(&self) -> LeverageMode {
+        self.mode
+    }
+
+    pub fn expense_ratio(&self) -> ExpenseRatio {
+        self.expense_ratio
+    }
+
     pub fn period(&self) -> Period {
         self.period
     }
+
+    pub fn stop(&self) -> Option<StopRule> {
+        self.stop
+    }
+
+    pub fn taxes(&self) -> Option<TaxableAccount> {
+        self.taxes
+    }
 }
 
 #[allow(dead_code)]
 impl PriceHistoryVariants {
-    pub fn new(price_history: PriceHistory, period: Period) -> PriceHistoryVariants {
-        let total_price_changes = leverage_amounts()
+    pub fn new(
+        market_history: &MarketHistory,
+        period: Period,
+        leverages: &LeverageGrid,
+        expense_ratios: &ExpenseRatioSchedule,
+        leverage_modes: &[LeverageMode],
+        trading_costs: &TradingCosts,
+        tracking: &OutcomeTracking,
+    ) -> PriceHistoryVariants {
+        let underlying_price_change = market_history.price_history().total();
+        let underlying_total_return = market_history.total_return();
+        let total_inflation = market_history.inflation().map(PriceHistory::total);
+        let path_trading_costs = trading_costs.along(market_history.price_history());
+        // Each variant comes first without a stop and then with each one, and each of those
+        // is followed by the same held in a taxable account
+        let descriptors: Vec<PriceHistoryDescriptor> = leverage_modes
             .iter()
-            .copied()
-            .map(|leverage| {
-                let expense_ratio = expense_ratio_for_leverage(leverage);
-                let price_history_variant = price_history
-                    .clone()
-                    .apply_modifier(PriceHistory::leverage_modifier(leverage))
-                    .apply_modifier(PriceHistory::expense_ratio_modifier(expense_ratio));
-
-                // println!(
-                //     "Leverage: {:.2}, Expense Ratio: {:.10}, {:+.5}",
-                //     leverage.amount(),
-                //     expense_ratio.amount(),
-                //     price_history_variant
-                // );
-                price_history_variant
+            .flat_map(|&mode| {
+                leverages.leverages().iter().flat_map(move |&leverage| {
+                    let expense_ratio = mode.expense_ratio(leverage, expense_ratios);
+                    let unstopped =
+                        PriceHistoryDescriptor::new(leverage, mode, expense_ratio, period);
+                    std::iter::once(unstopped)
+                        .chain(
+                            tracking
+                                .stops()
+                                .iter()
+                                .map(move |&stop| unstopped.with_stop(stop)),
+                        )
+                        .flat_map(move |untaxed| {
+                            std::iter::once(untaxed)
+                                .chain(tracking.taxes().map(|taxes| untaxed.with_taxes(taxes)))
+                        })
+                })
             })
-            .map(|price_history_variant| price_history_variant.total())
             .collect();
 
-        let descriptors = leverage_amounts()
-            .iter()
-            .copied()
-            .map(|leverage| PriceHistoryDescriptor { leverage, period })
-            .collect();
+        let mut total_price_changes = Vec::with_capacity(descriptors.len());
+        let mut price_returns = Vec::with_capacity(descriptors.len());
+        let mut real_price_changes = Vec::with_capacity(descriptors.len());
+        let mut ruins = Vec::with_capacity(descriptors.len());
+        let mut realized_leverages = Vec::with_capacity(descriptors.len());
+        let mut cost_drags = Vec::with_capacity(descriptors.len());
+        let mut cash_flow_outcomes = Vec::with_capacity(descriptors.len());
+        let mut retirement_outcomes = Vec::with_capacity(descriptors.len());
+        let mut tax_outcomes = Vec::with_capacity(descriptors.len());
+        let mut stop_outcomes = Vec::with_capacity(descriptors.len());
+        let lump_sum = CashFlows::new(1.0, 0.0, period);
+        // The unstopped variant's simulation, for the stopped ones after it
+        let mut simulated = None;
+        // The untaxed variant's path and what was done along it, for the taxed one after it
+        let mut untaxed: Option<(PriceHistory, Activity, Option<Ruin>)> = None;
+        for descriptor in descriptors.iter() {
+            if let Some(taxes) = descriptor.taxes() {
+                // Sold off and taxed at the end, while its price return and balances are
+                // left as they were before taxes
+                let (price_history_variant, activity, ruin) = untaxed
+                    .as_ref()
+                    .expect("Taxed variants follow their untaxed one");
+                let after_tax = taxes.apply(price_history_variant, activity, *ruin, &lump_sum);
+                let total_price_change =
+                    PriceChange::from(Percent::from_multiplier(after_tax.after_tax_wealth()));
+                total_price_changes.push(total_price_change);
+                real_price_changes.push(match total_inflation {
+                    Some(inflation) => total_price_change.deflate(inflation),
+                    None => total_price_change,
+                });
+
+                // Taxes are owed on nominal gains, so they're charged before deflating
+                let outcome = match tracking.cash_flows() {
+                    Some(cash_flows) => {
+                        taxes.apply(price_history_variant, activity, *ruin, &cash_flows)
+                    }
+                    None => after_tax,
+                };
+                tax_outcomes.push(Some(match total_inflation {
+                    Some(inflation) => outcome.deflate(inflation),
+                    None => outcome,
+                }));
+
+                repeat_last(&mut price_returns);
+                repeat_last(&mut ruins);
+                repeat_last(&mut realized_leverages);
+                repeat_last(&mut cost_drags);
+                repeat_last(&mut cash_flow_outcomes);
+                repeat_last(&mut retirement_outcomes);
+                repeat_last(&mut stop_outcomes);
+                continue;
+            }
+
+            let leverage = descriptor.leverage();
+            if descriptor.stop().is_none() {
+                simulated = Some(descriptor.mode().simulate(
+                    leverage,
+                    expense_ratios,
+                    trading_costs,
+                    market_history,
+                ));
+            }
+            let (unstopped, realized_leverage, unstopped_cost_drag, unstopped_activity) = simulated
+                .as_ref()
+                .expect("Stopped variants follow their unstopped one");
+            let stopped;
+            let (price_history_variant, activity, cost_drag, stop_outcome) = match descriptor.stop()
+            {
+                Some(stop) => {
+                    let (price_history, activity, cost_drag, outcome) = stop.apply(
+                        unstopped,
+                        unstopped_activity,
+                        *unstopped_cost_drag,
+                        &path_trading_costs,
+                        market_history.cash_rates(),
+                    );
+                    stopped = (price_history, activity);
+                    (&stopped.0, &stopped.1, cost_drag, Some(outcome))
+                }
+                None => (unstopped, unstopped_activity, *unstopped_cost_drag, None),
+            };
+            stop_outcomes.push(stop_outcome);
+
+            // println!(
+            //     "Leverage: {:.2}, Expense Ratio: {:.10}, {:+.5}",
+            //     leverage.amount(),
+            //     descriptor.expense_ratio().amount(),
+            //     price_history_variant
+            // );
+            let (total_price_change, ruin) =
+                track_ruin(price_history_variant, tracking.terminations());
+            total_price_changes.push(total_price_change);
+            // What the variant was paid comes out of the same pass
+            price_returns.push(match market_history.dividends(0) {
+                Some(_) => {
+                    let price_history = price_history_variant
+                        .clone()
+                        .apply_modifier(PriceHistory::dividend_withdrawal_modifier(activity));
+                    track_ruin(&price_history, tracking.terminations()).0
+                }
+                None => total_price_change,
+            });
+            real_price_changes.push(match total_inflation {
+                Some(inflation) => total_price_change.deflate(inflation),
+                None => total_price_change,
+            });
+            ruins.push(ruin);
+            realized_leverages.push(*realized_leverage);
+            cost_drags.push(cost_drag);
+            tax_outcomes.push(None);
+
+            // Balances are followed in today's money, so fixed amounts keep their value
+            let needs_balances = tracking.cash_flows().is_some() || tracking.retirement().is_some();
+            let deflated = match market_history.inflation() {
+                Some(inflation) if needs_balances => Some(
+                    price_history_variant
+                        .clone()
+                        .apply_modifier(PriceHistory::inflation_modifier(inflation)),
+                ),
+                _ => None,
+            };
+            let balances = deflated.as_ref().unwrap_or(price_history_variant);
+            cash_flow_outcomes.push(
+                tracking
+                    .cash_flows()
+                    .map(|cash_flows| cash_flows.apply(balances, ruin)),
+            );
+            retirement_outcomes.push(
+                tracking
+                    .retirement()
+                    .map(|retirement| retirement.apply(balances, ruin)),
+            );
+
+            if tracking.taxes().is_some() {
+                untaxed = Some((price_history_variant.clone(), activity.clone(), ruin));
+            }
+        }
 
         PriceHistoryVariants {
+            underlying_price_change,
+            underlying_total_return,
             total_price_changes,
+            price_returns,
+            real_price_changes,
+            ruins,
+            realized_leverages,
+            cost_drags,
+            cash_flow_outcomes,
+            retirement_outcomes,
+            tax_outcomes,
+            stop_outcomes,
             descriptors,
         }
     }
@@ -105,83 +441,348 @@ impl PriceHistoryVariants {
         &self.descriptors
     }
 
+    /// Total price change of the unleveraged path with no expenses, which every variant
+    /// is derived from. Leaves out dividends paid separately.
+    pub fn underlying_price_change(&self) -> PriceChange {
+        self.underlying_price_change
+    }
+
+    /// Total return of the unleveraged path with its dividends reinvested.
+    pub fn underlying_total_return(&self) -> PriceChange {
+        self.underlying_total_return
+    }
+
     pub fn total_price_changes(&self) -> &[PriceChange] {
         &self.total_price_changes
     }
-}
 
-fn expense_ratio_for_leverage(leverage: Leverage) -> ExpenseRatio {
-    match leverage.amount() {
-        amt if amt > 1.0 => ExpenseRatio::new(Percent::from_percent(0.93), Period::Years(1)),
-        _ => ExpenseRatio::zero(),
+    /// Each variant's total price change after inflation.
+    pub fn real_price_changes(&self) -> &[PriceChange] {
+        &self.real_price_changes
+    }
+
+    /// Each variant's total price change had its dividends been withdrawn as they were paid.
+    pub fn price_returns(&self) -> &[PriceChange] {
+        &self.price_returns
+    }
+
+    /// How each variant was wiped out or terminated, if it was.
+    pub fn ruins(&self) -> &[Option<Ruin>] {
+        &self.ruins
     }
+
+    pub fn realized_leverages(&self) -> &[RealizedLeverage] {
+        &self.realized_leverages
+    }
+
+    pub fn outcome(&self, index: usize) -> VariantOutcome {
+        VariantOutcome {
+            price_change: self.total_price_changes[index],
+            price_return: self.price_returns[index],
+            real_price_change: self.real_price_changes[index],
+            underlying_price_change: self.underlying_price_change,
+            ruin: self.ruins[index],
+            realized_leverage: self.realized_leverages[index],
+            cost_drag: self.cost_drags[index],
+            cash_flows: self.cash_flow_outcomes[index],
+            retirement: self.retirement_outcomes[index],
+            taxes: self.tax_outcomes[index],
+            stops: self.stop_outcomes[index],
+        }
+    }
+}
+
+fn repeat_last<T: Copy>(values: &mut Vec<T>) {
+    values.push(values[values.len() - 1]);
 }
 
 #[cfg(test)]
 mod test {
+    use crate::{
+        number::Percent,
+        pricing::{
+            Financing, PriceHistory, RateSeries, RebalanceSchedule, Rebalancing,
+            ScheduleInterpolation, TaxRates,
+        },
+    };
+
     use super::*;
 
     #[test]
     fn test_expense_ratio() {
         let no_ratio = ExpenseRatio::zero();
         let high_ratio = ExpenseRatio::new(Percent::from_percent(0.93), Period::Years(1));
+        let expense_ratio_for_leverage =
+            |amount| ExpenseRatioSchedule::default().expense_ratio(Leverage::new(amount));
 
-        assert_eq!(expense_ratio_for_leverage(Leverage::new(0.0)), no_ratio);
-        assert_eq!(expense_ratio_for_leverage(Leverage::new(0.5)), no_ratio);
-        assert_eq!(expense_ratio_for_leverage(Leverage::new(1.0)), no_ratio);
-        assert_eq!(expense_ratio_for_leverage(Leverage::new(1.5)), high_ratio);
-        assert_eq!(expense_ratio_for_leverage(Leverage::new(2.0)), high_ratio);
-        assert_eq!(expense_ratio_for_leverage(Leverage::new(3.0)), high_ratio);
-        assert_eq!(expense_ratio_for_leverage(Leverage::new(5.0)), high_ratio);
+        assert_eq!(expense_ratio_for_leverage(0.0), no_ratio);
+        assert_eq!(expense_ratio_for_leverage(0.5), no_ratio);
+        assert_eq!(expense_ratio_for_leverage(1.0), no_ratio);
+        assert_eq!(expense_ratio_for_leverage(1.5), high_ratio);
+        assert_eq!(expense_ratio_for_leverage(2.0), high_ratio);
+        assert_eq!(expense_ratio_for_leverage(3.0), high_ratio);
+        assert_eq!(expense_ratio_for_leverage(5.0), high_ratio);
+        assert_eq!(expense_ratio_for_leverage(-0.5), high_ratio);
+        assert_eq!(expense_ratio_for_leverage(-1.0), high_ratio);
+        assert_eq!(expense_ratio_for_leverage(-3.0), high_ratio);
     }
 
     #[test]
-    fn test_leverage_variants() {
-        let expected = vec![
-            Leverage::new(0.1),
-            Leverage::new(0.2),
-            Leverage::new(0.3),
-            Leverage::new(0.4),
-            Leverage::new(0.5),
-            Leverage::new(0.6),
-            Leverage::new(0.7),
-            Leverage::new(0.8),
-            Leverage::new(0.9),
-            Leverage::new(1.0),
-            Leverage::new(1.1),
-            Leverage::new(1.2),
-            Leverage::new(1.3),
-            Leverage::new(1.4),
-            Leverage::new(1.5),
-            Leverage::new(1.6),
-            Leverage::new(1.7),
-            Leverage::new(1.8),
-            Leverage::new(1.9),
-            Leverage::new(2.0),
-            Leverage::new(2.1),
-            Leverage::new(2.2),
-            Leverage::new(2.3),
-            Leverage::new(2.4),
-            Leverage::new(2.5),
-            Leverage::new(2.6),
-            Leverage::new(2.7),
-            Leverage::new(2.8),
-            Leverage::new(2.9),
-            Leverage::new(3.0),
-            Leverage::new(3.5),
-            Leverage::new(4.0),
-            Leverage::new(4.5),
-            Leverage::new(5.0),
-            Leverage::new(6.0),
-            Leverage::new(7.0),
-            Leverage::new(8.0),
-            Leverage::new(9.0),
-            Leverage::new(10.0),
-        ];
-        let actual = Vec::from(leverage_amounts());
-
-        for (i, expected_item) in expected.iter().enumerate() {
-            assert_eq!(Some(expected_item), actual.get(i))
+    fn test_expense_ratio_schedule() {
+        let schedule = ExpenseRatioSchedule::new(
+            &[
+                (Leverage::new(1.0), Percent::from_percent(0.03)),
+                (Leverage::new(2.0), Percent::from_percent(0.95)),
+                (Leverage::new(3.0), Percent::from_percent(0.91)),
+            ],
+            ScheduleInterpolation::Linear,
+        );
+        let annual_percent = |amount| schedule.annual_amount(Leverage::new(amount)).as_percent();
+
+        assert!((annual_percent(0.5) - 0.03).abs() < 1e-12);
+        assert!((annual_percent(1.5) - 0.49).abs() < 1e-12);
+        assert!((annual_percent(2.5) - 0.93).abs() < 1e-12);
+        assert!((annual_percent(4.0) - 0.91).abs() < 1e-12);
+
+        // The daily fee compounds back to the annual one
+        let expense_ratio = schedule.expense_ratio(Leverage::new(2.0));
+        let days = Period::Years(1).as_days() as i32;
+        let compounded = f64::powi(expense_ratio.multiplier(), days);
+        assert!((compounded - (1.0 - 0.0095)).abs() < 1e-12);
+        assert!((expense_ratio.annual_amount().as_percent() - 0.95).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_financing_cost() {
+        let period = Period::Days(Period::MARKET_DAYS_PER_YEAR);
+        let price_history: PriceHistory = vec![PriceChange::zero(); period.as_days() as usize]
+            .into_iter()
+            .collect();
+        let financing = Financing::new(
+            RateSeries::Constant(Percent::from_percent(3.0)),
+            Percent::from_percent(1.0),
+        );
+        let market_history =
+            MarketHistory::new(price_history, financing.daily_borrowing_rates(0, period))
+                .with_base_rates(financing.daily_base_rates(0, period));
+
+        let expense_ratios = ExpenseRatioSchedule::default();
+        let variants = PriceHistoryVariants::new(
+            &market_history,
+            period,
+            &LeverageGrid::new(&[Leverage::new(-1.0), Leverage::new(1.0), Leverage::new(2.0)]),
+            &expense_ratios,
+            &[LeverageMode::DailyReset],
+            &TradingCosts::None,
+            &OutcomeTracking::default(),
+        );
+        let total_for = |amount| {
+            let i = variants
+                .descriptors()
+                .iter()
+                .position(|descriptor| descriptor.leverage() == Leverage::new(amount))
+                .unwrap();
+            variants.total_price_changes()[i]
+                .percent_change()
+                .as_decimal()
+        };
+
+        assert!(total_for(1.0).abs() < 1e-12);
+        // Borrowing one unit for a year costs the rate plus spread, on top of expenses
+        let daily_cost = f64::powf(1.04, 1.0 / period.as_days() as f64) - 1.0;
+        let daily_expense = expense_ratios.expense_ratio(Leverage::new(2.0)).amount();
+        let daily_multiplier = (1.0 - daily_cost) * daily_expense.as_multiplier();
+        let expected = f64::powi(daily_multiplier, period.as_days() as i32) - 1.0;
+        assert!((total_for(2.0) - expected).abs() < 1e-12);
+
+        // Shorting one unit costs the swap spread, the borrowing rate over the base rate
+        let daily_fee = daily_cost - (f64::powf(1.03, 1.0 / period.as_days() as f64) - 1.0);
+        let daily_expense = expense_ratios.expense_ratio(Leverage::new(-1.0)).amount();
+        let daily_multiplier = (1.0 - daily_fee) * daily_expense.as_multiplier();
+        let expected = f64::powi(daily_multiplier, period.as_days() as i32) - 1.0;
+        assert!((total_for(-1.0) - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_dividends() {
+        // Prices never move, but the index yields 1bp a day
+        let period = Period::Days(Period::MARKET_DAYS_PER_YEAR);
+        let days = period.as_days() as usize;
+        let dividends = vec![PriceChange::from(Percent::from_decimal(0.0001)); days]
+            .into_iter()
+            .collect::<PriceHistory>();
+        let market_history = MarketHistory::from(
+            vec![PriceChange::zero(); days]
+                .into_iter()
+                .collect::<PriceHistory>(),
+        )
+        .with_dividends(dividends);
+        let rebalanced = LeverageMode::Rebalanced(Rebalancing::new(
+            RebalanceSchedule::Periodic(Period::Days(1)),
+            Percent::zero(),
+        ));
+
+        let variants = PriceHistoryVariants::new(
+            &market_history,
+            period,
+            &LeverageGrid::new(&[Leverage::new(2.0)]),
+            &ExpenseRatioSchedule::flat(Percent::zero()),
+            &[LeverageMode::DailyReset, rebalanced],
+            &TradingCosts::None,
+            &OutcomeTracking::default(),
+        );
+        let total_return = |index: usize| {
+            variants
+                .outcome(index)
+                .price_change()
+                .percent_change()
+                .as_decimal()
+        };
+        let compounded = |daily: f64| f64::powi(1.0 + daily, days as i32) - 1.0;
+
+        // The fund only passes on the unleveraged yield, while shares held on borrowed money
+        // are paid in full
+        assert!((total_return(0) - compounded(0.0001)).abs() < 1e-12);
+        assert!((total_return(1) - compounded(0.0002)).abs() < 1e-12);
+        // Prices never moved, so there's nothing left once what was paid is taken out
+        for index in 0..2 {
+            let price_return = variants.outcome(index).price_return().percent_change();
+            assert!(price_return.as_decimal().abs() < 1e-12);
         }
+        let underlying_total_return = variants.underlying_total_return().percent_change();
+        assert!((underlying_total_return.as_decimal() - compounded(0.0001)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_cash_rates() {
+        // Prices never move, but cash pays 1bp a day
+        let period = Period::Days(Period::MARKET_DAYS_PER_YEAR);
+        let days = period.as_days() as usize;
+        let market_history = MarketHistory::from(
+            vec![PriceChange::zero(); days]
+                .into_iter()
+                .collect::<PriceHistory>(),
+        )
+        .with_cash_rates(vec![Percent::from_decimal(0.0001); days]);
+        let rebalanced = LeverageMode::Rebalanced(Rebalancing::new(
+            RebalanceSchedule::Periodic(Period::Days(1)),
+            Percent::zero(),
+        ));
+
+        let variants = PriceHistoryVariants::new(
+            &market_history,
+            period,
+            &LeverageGrid::new(&[Leverage::new(0.5)]),
+            &ExpenseRatioSchedule::flat(Percent::zero()),
+            &[LeverageMode::DailyReset, rebalanced],
+            &TradingCosts::None,
+            &OutcomeTracking::default(),
+        );
+        let compounded = f64::powi(1.0 + 0.00005, days as i32) - 1.0;
+
+        // Half of either is held in cash
+        for index in 0..2 {
+            let total_return = variants
+                .outcome(index)
+                .price_change()
+                .percent_change()
+                .as_decimal();
+            assert!((total_return - compounded).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_real_returns() {
+        // Doubles over a year while prices rise 25%
+        let period = Period::Days(Period::MARKET_DAYS_PER_YEAR);
+        let days = period.as_days() as usize;
+        let price_history = std::iter::once(PriceChange::from(Percent::from_percent(100.0)))
+            .chain(std::iter::repeat_n(PriceChange::zero(), days - 1))
+            .collect::<PriceHistory>();
+        let inflation = RateSeries::Constant(Percent::from_percent(25.0))
+            .daily_rates(0, period)
+            .into_iter()
+            .map(PriceChange::from)
+            .collect::<PriceHistory>();
+        let market_history = MarketHistory::new(price_history, vec![Percent::zero(); days])
+            .with_inflation(inflation);
+
+        let variants = PriceHistoryVariants::new(
+            &market_history,
+            period,
+            &LeverageGrid::new(&[Leverage::new(1.0)]),
+            &ExpenseRatioSchedule::flat(Percent::zero()),
+            &[LeverageMode::DailyReset],
+            &TradingCosts::None,
+            &OutcomeTracking::new(
+                Vec::new(),
+                Some(CashFlows::new(100.0, 0.0, period)),
+                None,
+                None,
+                Vec::new(),
+            ),
+        );
+        let outcome = variants.outcome(0);
+
+        assert!((outcome.price_change().percent_change().as_decimal() - 1.0).abs() < 1e-12);
+        assert!((outcome.real_price_change().percent_change().as_decimal() - 0.6).abs() < 1e-9);
+        let ending_balance = outcome.cash_flows().unwrap().ending_balance();
+        assert!((ending_balance - 160.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_taxed_variants() {
+        // Rises steadily for a year
+        let period = Period::Days(Period::MARKET_DAYS_PER_YEAR);
+        let days = period.as_days() as usize;
+        let market_history = MarketHistory::from(
+            vec![PriceChange::from(Percent::from_decimal(0.001)); days]
+                .into_iter()
+                .collect::<PriceHistory>(),
+        );
+        let rebalanced = LeverageMode::Rebalanced(Rebalancing::new(
+            RebalanceSchedule::Periodic(Period::Days(1)),
+            Percent::zero(),
+        ));
+        let taxes = TaxableAccount::new(TaxRates::new(
+            Percent::from_percent(40.0),
+            Percent::from_percent(20.0),
+            Percent::from_percent(20.0),
+        ));
+
+        let variants = PriceHistoryVariants::new(
+            &market_history,
+            period,
+            &LeverageGrid::new(&[Leverage::new(0.5)]),
+            &ExpenseRatioSchedule::flat(Percent::zero()),
+            &[LeverageMode::DailyReset, rebalanced],
+            &TradingCosts::None,
+            &OutcomeTracking::new(Vec::new(), None, None, Some(taxes), Vec::new()),
+        );
+        let descriptors = variants.descriptors();
+        assert_eq!(descriptors.len(), 4);
+        assert_eq!(descriptors[0].taxes(), None);
+        assert_eq!(descriptors[1].taxes(), Some(taxes));
+        let kept = |index: usize| {
+            variants
+                .outcome(index + 1)
+                .price_change()
+                .percent_change()
+                .as_decimal()
+                / variants
+                    .outcome(index)
+                    .price_change()
+                    .percent_change()
+                    .as_decimal()
+        };
+
+        // Holding the fund defers every gain to the end, when it's long-term, while trimming
+        // the stock back every day realizes short-term gains along the way
+        assert!((kept(0) - 0.8).abs() < 1e-9);
+        assert!(kept(2) < kept(0));
+        assert_eq!(
+            variants.outcome(3).price_return(),
+            variants.outcome(2).price_return()
+        );
     }
 }
@@ -1,4 +1,7 @@
-use crate::pricing::{PriceChange, PriceHistory};
+use crate::{
+    pricing::{PriceChange, PriceHistory},
+    random::UniformSource,
+};
 
 use super::PricingStrategy;
 
@@ -17,7 +20,12 @@ impl AlternatingPricingStrategy {
 }
 
 impl PricingStrategy for AlternatingPricingStrategy {
-    fn calculate_price_change(&self, period: u64, _price_history: &PriceHistory) -> PriceChange {
+    fn calculate_price_change(
+        &self,
+        period: u64,
+        _price_history: &PriceHistory,
+        _uniforms: &mut dyn UniformSource,
+    ) -> PriceChange {
         let choice = period as usize % self.price_change_options.len();
         self.price_change_options[choice]
     }
@@ -0,0 +1,258 @@
+use crate::{
+    number::Percent,
+    pricing::{PriceChange, PriceHistory},
+    random::{standard_normal_quantile, GeneralizedPareto, UniformSource},
+};
+
+use super::PricingStrategy;
+
+/// Width of the Gaussian kernel placed on every historical day, in log return units.
+#[derive(Debug, Clone, Copy)]
+pub enum Bandwidth {
+    /// Silverman's rule of thumb for the sample.
+    Silverman,
+    Fixed(f64),
+}
+
+/// Replaces each tail of the distribution beyond the given fraction of days with a
+/// generalized Pareto distribution fitted to the historical exceedances. A tail with fewer
+/// than `MIN_EXCEEDANCES` days in it, or one that can't be fitted, is left smoothed.
+#[derive(Debug, Clone, Copy)]
+pub struct TailExtension {
+    tail_fraction: Percent,
+}
+
+impl TailExtension {
+    pub fn new(tail_fraction: Percent) -> Self {
+        let decimal = tail_fraction.as_decimal();
+        if decimal <= 0.0 || decimal >= 0.5 {
+            panic!("Invalid tail fraction: {}", tail_fraction);
+        }
+        Self { tail_fraction }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FittedTail {
+    fraction: f64,
+    threshold: f64,
+    distribution: GeneralizedPareto,
+}
+
+/// Samples a historical day and perturbs it with kernel noise, drawing from a smoothed
+/// version of the empirical distribution. Smoothing happens on log returns, so a day can
+/// never lose more than everything, and the result is rescaled to keep the historical
+/// variance. With a tail extension, the tails come from the fitted Pareto distributions
+/// instead, so days beyond the worst (or best) in history can occur.
+#[derive(Debug, Clone)]
+pub struct KernelDensityPricingStrategy {
+    log_returns: Vec<f64>,
+    mean: f64,
+    bandwidth: f64,
+    shrinkage: f64,
+    lower_tail: Option<FittedTail>,
+    upper_tail: Option<FittedTail>,
+}
+
+impl KernelDensityPricingStrategy {
+    pub fn new(
+        price_change_options: &[PriceChange],
+        bandwidth: Bandwidth,
+        tail_extension: Option<TailExtension>,
+    ) -> Self {
+        let mut log_returns: Vec<f64> = price_change_options
+            .iter()
+            .map(|price_change| price_change.percent_change().as_multiplier().ln())
+            .collect();
+        log_returns.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let count = log_returns.len() as f64;
+        let mean = log_returns.iter().sum::<f64>() / count;
+        let variance = log_returns
+            .iter()
+            .map(|log_return| f64::powi(log_return - mean, 2))
+            .sum::<f64>()
+            / (count - 1.0);
+
+        let bandwidth = match bandwidth {
+            Bandwidth::Fixed(bandwidth) => bandwidth,
+            Bandwidth::Silverman => {
+                let quantile = |p: f64| log_returns[(p * count) as usize];
+                let spread = f64::min(variance.sqrt(), (quantile(0.75) - quantile(0.25)) / 1.34);
+                0.9 * spread * count.powf(-0.2)
+            }
+        };
+
+        let (lower_tail, upper_tail) = match tail_extension {
+            Some(tail_extension) => {
+                let fraction = tail_extension.tail_fraction.as_decimal();
+                let tail_count = (fraction * count) as usize;
+
+                let lower_threshold = log_returns[tail_count];
+                let lower_exceedances: Vec<f64> = log_returns[..tail_count]
+                    .iter()
+                    .map(|log_return| lower_threshold - log_return)
+                    .collect();
+
+                let upper_threshold = log_returns[log_returns.len() - 1 - tail_count];
+                let upper_exceedances: Vec<f64> = log_returns[log_returns.len() - tail_count..]
+                    .iter()
+                    .map(|log_return| log_return - upper_threshold)
+                    .collect();
+
+                let fitted_tail = |threshold, exceedances: &[f64]| {
+                    GeneralizedPareto::fit(exceedances).map(|distribution| FittedTail {
+                        fraction,
+                        threshold,
+                        distribution,
+                    })
+                };
+                (
+                    fitted_tail(lower_threshold, &lower_exceedances),
+                    fitted_tail(upper_threshold, &upper_exceedances),
+                )
+            }
+            None => (None, None),
+        };
+
+        Self {
+            log_returns,
+            mean,
+            bandwidth,
+            shrinkage: 1.0 / f64::sqrt(1.0 + bandwidth * bandwidth / variance),
+            lower_tail,
+            upper_tail,
+        }
+    }
+
+    pub fn bandwidth(&self) -> f64 {
+        self.bandwidth
+    }
+
+    pub fn lower_tail(&self) -> Option<GeneralizedPareto> {
+        self.lower_tail.map(|tail| tail.distribution)
+    }
+
+    pub fn upper_tail(&self) -> Option<GeneralizedPareto> {
+        self.upper_tail.map(|tail| tail.distribution)
+    }
+
+    fn sample_log_return(&self, quantile: f64, noise: f64) -> f64 {
+        if let Some(tail) = self.lower_tail.filter(|tail| quantile < tail.fraction) {
+            let exceedance = tail.distribution.exceedance(quantile / tail.fraction);
+            return tail.threshold - exceedance;
+        }
+
+        if let Some(tail) = self
+            .upper_tail
+            .filter(|tail| quantile > 1.0 - tail.fraction)
+        {
+            let exceedance = tail
+                .distribution
+                .exceedance((1.0 - quantile) / tail.fraction);
+            return tail.threshold + exceedance;
+        }
+
+        let count = self.log_returns.len();
+        let choice = ((quantile * count as f64) as usize).min(count - 1);
+        let smoothed = self.log_returns[choice] - self.mean + self.bandwidth * noise;
+        self.mean + self.shrinkage * smoothed
+    }
+}
+
+impl PricingStrategy for KernelDensityPricingStrategy {
+    fn calculate_price_change(
+        &self,
+        _period: u64,
+        _price_history: &PriceHistory,
+        uniforms: &mut dyn UniformSource,
+    ) -> PriceChange {
+        // Always take both draws so every day uses the same quasi-random dimensions
+        let quantile = uniforms.next_uniform();
+        let noise = standard_normal_quantile(uniforms.next_uniform());
+        let log_return = self.sample_log_return(quantile, noise);
+        Percent::from_multiplier(log_return.exp()).into()
+    }
+
+    fn uniforms_per_price_change(&self) -> u64 {
+        2
+    }
+
+    fn expected_price_change(&self) -> Option<PriceChange> {
+        // Only the purely smoothed distribution has a closed form mean
+        if self.lower_tail.is_some() || self.upper_tail.is_some() {
+            return None;
+        }
+
+        let kernel_spread = self.shrinkage * self.bandwidth;
+        let kernel_factor = f64::exp(kernel_spread * kernel_spread / 2.0);
+        let multiplier_sum: f64 = self
+            .log_returns
+            .iter()
+            .map(|log_return| f64::exp(self.mean + self.shrinkage * (log_return - self.mean)))
+            .sum();
+        let multiplier = kernel_factor * multiplier_sum / self.log_returns.len() as f64;
+        Some(Percent::from_multiplier(multiplier).into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::random::{PseudoRandomSequence, UniformSequence};
+
+    use super::*;
+
+    #[test]
+    fn test_kernel_density_sampling() {
+        let history = |days: usize| {
+            (0..days)
+                .map(|day| standard_normal_quantile((day as f64 + 0.5) / days as f64) * 0.01)
+                .map(|log_return| PriceChange::from(Percent::from_multiplier(log_return.exp())))
+                .collect::<Vec<_>>()
+        };
+        let days = 500;
+        // The historical log returns are symmetric about zero
+        let variance = history(days)
+            .iter()
+            .map(|change| change.percent_change().as_multiplier().ln().powi(2))
+            .sum::<f64>()
+            / (days as f64 - 1.0);
+
+        // Smoothing keeps the historical mean and variance of log returns
+        let smoothed =
+            KernelDensityPricingStrategy::new(&history(days), Bandwidth::Silverman, None);
+        let samples = smoothed
+            .calculate_price_history(0..100_000, &mut PseudoRandomSequence::new(3).path_source(0))
+            .iter()
+            .map(|change| change.percent_change().as_multiplier())
+            .collect::<Vec<_>>();
+        assert!(samples
+            .iter()
+            .all(|multiplier| multiplier.is_finite() && *multiplier > 0.0));
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let expected = smoothed.expected_price_change().unwrap();
+        assert!((mean - expected.percent_change().as_multiplier()).abs() < 1e-4);
+        let sample_variance = samples
+            .iter()
+            .map(|multiplier| multiplier.ln().powi(2))
+            .sum::<f64>()
+            / samples.len() as f64;
+        assert!((sample_variance / variance - 1.0).abs() < 0.02);
+
+        // 5% of 500 days leaves 25 days in each tail to fit, and the fitted tails reach past
+        // the worst and best days in history
+        let extension = Some(TailExtension::new(Percent::from_percent(5.0)));
+        let tailed =
+            KernelDensityPricingStrategy::new(&history(days), Bandwidth::Silverman, extension);
+        assert!(tailed.lower_tail().is_some() && tailed.upper_tail().is_some());
+        let worst = standard_normal_quantile(0.5 / 500.0) * 0.01;
+        assert!(tailed.sample_log_return(1e-6, 0.0) < worst);
+        assert!(tailed.sample_log_return(1.0 - 1e-6, 0.0) > -worst);
+
+        // 5% of 100 days is too few to fit, so those tails stay smoothed
+        let short =
+            KernelDensityPricingStrategy::new(&history(100), Bandwidth::Silverman, extension);
+        assert!(short.lower_tail().is_none() && short.upper_tail().is_none());
+        assert!(short.expected_price_change().is_some());
+    }
+}
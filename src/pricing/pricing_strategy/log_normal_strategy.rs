@@ -0,0 +1,56 @@
+use crate::{
+    number::Percent,
+    pricing::{PriceChange, PriceHistory},
+    random::{standard_normal_quantile, UniformSource},
+};
+
+use super::PricingStrategy;
+
+/// Parametric strategy drawing daily log returns from a normal distribution fitted to the
+/// historical options.
+#[derive(Debug, Clone, Copy)]
+pub struct LogNormalPricingStrategy {
+    mean: f64,
+    stdev: f64,
+}
+
+impl LogNormalPricingStrategy {
+    pub fn new(price_change_options: &[PriceChange]) -> Self {
+        let log_returns: Vec<f64> = price_change_options
+            .iter()
+            .map(|price_change| price_change.percent_change().as_multiplier().ln())
+            .collect();
+
+        let count = log_returns.len() as f64;
+        let mean = log_returns.iter().sum::<f64>() / count;
+        let variance = log_returns
+            .iter()
+            .map(|log_return| f64::powi(log_return - mean, 2))
+            .sum::<f64>()
+            / (count - 1.0);
+
+        Self::with_parameters(mean, variance.sqrt())
+    }
+
+    pub fn with_parameters(mean: f64, stdev: f64) -> Self {
+        Self { mean, stdev }
+    }
+}
+
+impl PricingStrategy for LogNormalPricingStrategy {
+    fn calculate_price_change(
+        &self,
+        _period: u64,
+        _price_history: &PriceHistory,
+        uniforms: &mut dyn UniformSource,
+    ) -> PriceChange {
+        let shock = standard_normal_quantile(uniforms.next_uniform());
+        let log_return = self.mean + self.stdev * shock;
+        Percent::from_multiplier(log_return.exp()).into()
+    }
+
+    fn expected_price_change(&self) -> Option<PriceChange> {
+        let multiplier = f64::exp(self.mean + self.stdev * self.stdev / 2.0);
+        Some(Percent::from_multiplier(multiplier).into())
+    }
+}
@@ -1,7 +1,16 @@
 mod alternating_strategy;
+mod kernel_density_strategy;
+mod log_normal_strategy;
+mod paired_sampling_strategy;
+mod path_sampling;
 mod sampling_strategy;
 mod strategy;
 
+#[allow(unused_imports)]
 pub use alternating_strategy::AlternatingPricingStrategy;
+pub use kernel_density_strategy::{Bandwidth, KernelDensityPricingStrategy, TailExtension};
+pub use log_normal_strategy::LogNormalPricingStrategy;
+pub use paired_sampling_strategy::PairedSamplingPricingStrategy;
+pub use path_sampling::PathSampling;
 pub use sampling_strategy::SamplingPricingStrategy;
 pub use strategy::PricingStrategy;
@@ -0,0 +1,99 @@
+use crate::{
+    pricing::{PriceChange, PriceHistory},
+    random::UniformSource,
+};
+
+use super::PricingStrategy;
+
+/// Samples another asset's daily price changes from the same historical days that
+/// `SamplingPricingStrategy` picks for the index, given the same uniforms. Both assets then
+/// move together as they did on those days, keeping their correlation.
+#[derive(Debug, Clone)]
+pub struct PairedSamplingPricingStrategy {
+    // Ordered like the index's options, so the same uniform lands on the same day
+    price_change_options: Vec<PriceChange>,
+}
+
+impl PairedSamplingPricingStrategy {
+    /// `index_price_changes` and `price_changes` must line up day by day.
+    pub fn new(index_price_changes: &[PriceChange], price_changes: &[PriceChange]) -> Self {
+        if index_price_changes.len() != price_changes.len() {
+            panic!(
+                "Paired assets need the same days, got {} and {}",
+                index_price_changes.len(),
+                price_changes.len()
+            );
+        }
+
+        let mut days = index_price_changes
+            .iter()
+            .zip(price_changes)
+            .collect::<Vec<_>>();
+        days.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap());
+        Self {
+            price_change_options: days.into_iter().map(|(_, &paired)| paired).collect(),
+        }
+    }
+}
+
+impl PricingStrategy for PairedSamplingPricingStrategy {
+    fn calculate_price_change(
+        &self,
+        _period: u64,
+        _price_history: &PriceHistory,
+        uniforms: &mut dyn UniformSource,
+    ) -> PriceChange {
+        let count = self.price_change_options.len();
+        let choice = (uniforms.next_uniform() * count as f64) as usize;
+        self.price_change_options[choice.min(count - 1)]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        number::Percent,
+        pricing::{PathSampling, Period, SamplingPricingStrategy},
+        random::PseudoRandomSequence,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_paired_days_line_up() {
+        // The second asset moves by exactly half the index on every day
+        let index = [1.0, -2.0, 3.0, -4.0, 0.5]
+            .iter()
+            .map(|&change| PriceChange::from(Percent::from_percent(change)))
+            .collect::<Vec<_>>();
+        let halved = index
+            .iter()
+            .map(|change| {
+                PriceChange::from(Percent::from_decimal(
+                    change.percent_change().as_decimal() / 2.0,
+                ))
+            })
+            .collect::<Vec<_>>();
+        let sequence = PseudoRandomSequence::new(7);
+        let period = Period::Days(50);
+
+        let index_paths = PathSampling::Antithetic.price_histories(
+            &SamplingPricingStrategy::new(&index),
+            &sequence,
+            3,
+            period,
+        );
+        let paired_paths = PathSampling::Antithetic.price_histories(
+            &PairedSamplingPricingStrategy::new(&index, &halved),
+            &sequence,
+            3,
+            period,
+        );
+        for (index_path, paired_path) in index_paths.iter().zip(&paired_paths) {
+            for (index_change, paired_change) in index_path.iter().zip(paired_path.iter()) {
+                let expected = index_change.percent_change().as_decimal() / 2.0;
+                assert!((paired_change.percent_change().as_decimal() - expected).abs() < 1e-12);
+            }
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{
+    pricing::{Period, PriceHistory},
+    random::{MirroredSource, UniformSequence},
+};
+
+use super::PricingStrategy;
+
+/// How simulated paths are drawn from a uniform sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathSampling {
+    /// Every path gets its own draws.
+    Independent,
+    /// Each draw yields a path and its mirror, built from reflected uniforms. The two
+    /// paths are negatively correlated, so they must be averaged before estimating error.
+    Antithetic,
+}
+
+impl PathSampling {
+    pub fn paths_per_draw(&self) -> u64 {
+        match self {
+            PathSampling::Independent => 1,
+            PathSampling::Antithetic => 2,
+        }
+    }
+
+    pub fn price_histories<P, S>(
+        &self,
+        strategy: &P,
+        sequence: &S,
+        draw: u64,
+        period: Period,
+    ) -> Vec<PriceHistory>
+    where
+        P: PricingStrategy,
+        S: UniformSequence,
+    {
+        let days = 0..period.as_days();
+        match self {
+            PathSampling::Independent => {
+                let mut uniforms = sequence.path_source(draw);
+                vec![strategy.calculate_price_history(days, &mut uniforms)]
+            }
+            PathSampling::Antithetic => {
+                let mut uniforms = sequence.path_source(draw);
+                let mut mirrored_uniforms = MirroredSource::new(sequence.path_source(draw));
+                vec![
+                    strategy.calculate_price_history(days.clone(), &mut uniforms),
+                    strategy.calculate_price_history(days, &mut mirrored_uniforms),
+                ]
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        pricing::LogNormalPricingStrategy,
+        random::{HaltonSequence, PseudoRandomSequence},
+    };
+
+    use super::*;
+
+    // Root mean squared error of the mean total multiplier over runs with different seeds
+    fn mean_error<S>(sequence: impl Fn(u64) -> S) -> f64
+    where
+        S: UniformSequence,
+    {
+        let strategy = LogNormalPricingStrategy::with_parameters(0.0005, 0.012);
+        let period = Period::Days(10);
+        let expected = strategy
+            .expected_total_price_change(period)
+            .unwrap()
+            .percent_change()
+            .as_multiplier();
+
+        let seeds = 20;
+        let paths = 1024;
+        let squared_errors = (0..seeds)
+            .map(|seed| {
+                let sequence = sequence(seed);
+                let mean = (0..paths)
+                    .flat_map(|draw| {
+                        PathSampling::Independent
+                            .price_histories(&strategy, &sequence, draw, period)
+                    })
+                    .map(|price_history| price_history.total().percent_change().as_multiplier())
+                    .sum::<f64>()
+                    / paths as f64;
+                f64::powi(mean - expected, 2)
+            })
+            .sum::<f64>();
+        f64::sqrt(squared_errors / seeds as f64)
+    }
+
+    #[test]
+    fn test_halton_paths_converge_faster() {
+        let pseudo_random_error = mean_error(PseudoRandomSequence::new);
+        let halton_error = mean_error(|seed| HaltonSequence::new(seed, 10));
+
+        // Pseudo-random errors shrink like 1/sqrt(n), low-discrepancy ones closer to 1/n
+        assert!(halton_error < pseudo_random_error / 4.0);
+    }
+}
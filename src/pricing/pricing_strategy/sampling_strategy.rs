@@ -1,27 +1,48 @@
-use rand::Rng;
-
-use crate::pricing::{PriceChange, PriceHistory};
+use crate::{
+    number::Percent,
+    pricing::{PriceChange, PriceHistory},
+    random::UniformSource,
+};
 
 use super::PricingStrategy;
 
 #[derive(Debug, Clone)]
 pub struct SamplingPricingStrategy {
+    // Sorted so a uniform draw picks the option at that quantile, which lets mirrored
+    // draws pick from the opposite end of the distribution.
     price_change_options: Vec<PriceChange>,
 }
 
 #[allow(dead_code)]
 impl SamplingPricingStrategy {
     pub fn new(price_change_options: &[PriceChange]) -> Self {
+        let mut price_change_options = Vec::from(price_change_options);
+        price_change_options.sort_by(|a, b| a.partial_cmp(b).unwrap());
         Self {
-            price_change_options: Vec::from(price_change_options),
+            price_change_options,
         }
     }
 }
 
 impl PricingStrategy for SamplingPricingStrategy {
-    fn calculate_price_change(&self, _period: u64, _price_history: &PriceHistory) -> PriceChange {
-        let range = 0..self.price_change_options.len();
-        let choice = rand::thread_rng().gen_range(range);
-        self.price_change_options[choice]
+    fn calculate_price_change(
+        &self,
+        _period: u64,
+        _price_history: &PriceHistory,
+        uniforms: &mut dyn UniformSource,
+    ) -> PriceChange {
+        let count = self.price_change_options.len();
+        let choice = (uniforms.next_uniform() * count as f64) as usize;
+        self.price_change_options[choice.min(count - 1)]
+    }
+
+    fn expected_price_change(&self) -> Option<PriceChange> {
+        let decimal_sum: f64 = self
+            .price_change_options
+            .iter()
+            .map(|price_change| price_change.percent_change().as_decimal())
+            .sum();
+        let decimal = decimal_sum / self.price_change_options.len() as f64;
+        Some(Percent::from_decimal(decimal).into())
     }
 }
@@ -2,19 +2,50 @@ use std::fmt;
 
 use fmt::Debug;
 
-use crate::pricing::{PriceChange, PriceHistory};
+use crate::{
+    number::Percent,
+    pricing::{Period, PriceChange, PriceHistory},
+    random::UniformSource,
+};
 
 pub trait PricingStrategy: Debug + Send + Sync {
-    fn calculate_price_change(&self, period: u64, price_history: &PriceHistory) -> PriceChange;
+    fn calculate_price_change(
+        &self,
+        period: u64,
+        price_history: &PriceHistory,
+        uniforms: &mut dyn UniformSource,
+    ) -> PriceChange;
 
-    fn calculate_price_history<R>(&self, range: R) -> PriceHistory
+    /// How many uniform draws each price change takes, which decides how days map onto the
+    /// dimensions of a quasi-random sequence.
+    fn uniforms_per_price_change(&self) -> u64 {
+        1
+    }
+
+    /// The mean daily price change, when the strategy draws independent days from a
+    /// known distribution.
+    fn expected_price_change(&self) -> Option<PriceChange> {
+        None
+    }
+
+    /// The mean total price change over a period, following from independent days.
+    fn expected_total_price_change(&self, period: Period) -> Option<PriceChange> {
+        let daily_multiplier = self
+            .expected_price_change()?
+            .percent_change()
+            .as_multiplier();
+        let total_multiplier = f64::powf(daily_multiplier, period.as_days() as f64);
+        Some(Percent::from_multiplier(total_multiplier).into())
+    }
+
+    fn calculate_price_history<R>(&self, range: R, uniforms: &mut dyn UniformSource) -> PriceHistory
     where
         R: IntoIterator<Item = u64>,
     {
         let mut price_history = PriceHistory::new();
 
         for period in range {
-            let price_change = Self::calculate_price_change(self, period, &price_history);
+            let price_change = Self::calculate_price_change(self, period, &price_history, uniforms);
             price_history.add(price_change);
         }
 
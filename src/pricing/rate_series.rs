@@ -0,0 +1,104 @@
+use crate::{
+    io::read_lines,
+    number::Percent,
+    random::{standard_normal_quantile, PseudoRandomSequence, UniformSequence, UniformSource},
+};
+
+use super::{Period, PriceChange};
+
+/// A series of annual interest rates, one for each simulated day.
+#[derive(Debug, Clone)]
+pub enum RateSeries {
+    Constant(Percent),
+    /// Rates read in order, holding the last one once the series runs out.
+    Historical(Vec<Percent>),
+    /// A fresh mean reverting rate path for every simulated path.
+    Simulated(RateProcess),
+}
+
+impl RateSeries {
+    /// Reads one annual rate per line, as a decimal.
+    pub fn from_file(file_path: &str) -> Option<RateSeries> {
+        let rates: Vec<Percent> = read_lines(file_path)?
+            .map(|line| line.replace(|c: char| !c.is_ascii(), ""))
+            .filter_map(|line| line.trim().parse::<f64>().ok())
+            .map(Percent::from_decimal)
+            .collect();
+
+        match rates.is_empty() {
+            true => None,
+            false => Some(RateSeries::Historical(rates)),
+        }
+    }
+
+    pub fn annual_rates(&self, path: u64, period: Period) -> Vec<Percent> {
+        let days = period.as_days() as usize;
+        match self {
+            RateSeries::Constant(rate) => vec![*rate; days],
+            RateSeries::Historical(rates) => (0..days)
+                .map(|day| rates[day.min(rates.len() - 1)])
+                .collect(),
+            RateSeries::Simulated(process) => process.annual_rates(path, days),
+        }
+    }
+
+    /// The rate earned each day, compounding to the annual rate over a year.
+    pub fn daily_rates(&self, path: u64, period: Period) -> Vec<Percent> {
+        self.annual_rates(path, period)
+            .into_iter()
+            .map(to_daily_rate)
+            .collect()
+    }
+}
+
+/// Vasicek short rate model, `dr = speed * (mean - r) dt + volatility * dW`, stepped daily
+/// and floored at zero.
+#[derive(Debug, Clone, Copy)]
+pub struct RateProcess {
+    initial: Percent,
+    long_run_mean: Percent,
+    reversion_speed: f64,
+    volatility: Percent,
+    sequence: PseudoRandomSequence,
+}
+
+impl RateProcess {
+    pub fn new(
+        initial: Percent,
+        long_run_mean: Percent,
+        reversion_speed: f64,
+        volatility: Percent,
+        seed: u64,
+    ) -> Self {
+        Self {
+            initial,
+            long_run_mean,
+            reversion_speed,
+            volatility,
+            sequence: PseudoRandomSequence::new(seed),
+        }
+    }
+
+    fn annual_rates(&self, path: u64, days: usize) -> Vec<Percent> {
+        let step = 1.0 / Period::MARKET_DAYS_PER_YEAR as f64;
+        let mut uniforms = self.sequence.path_source(path);
+        let mut rate = self.initial.as_decimal();
+
+        (0..days)
+            .map(|_| {
+                let current = Percent::from_decimal(rate);
+                let shock = standard_normal_quantile(uniforms.next_uniform());
+                let drift = self.reversion_speed * (self.long_run_mean.as_decimal() - rate) * step;
+                let diffusion = self.volatility.as_decimal() * step.sqrt() * shock;
+                rate = f64::max(rate + drift + diffusion, 0.0);
+                current
+            })
+            .collect()
+    }
+}
+
+pub fn to_daily_rate(annual_rate: Percent) -> Percent {
+    PriceChange::from(annual_rate)
+        .total_return(Period::Days(1))
+        .percent_change()
+}
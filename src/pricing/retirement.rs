@@ -0,0 +1,216 @@
+use crate::number::Percent;
+
+use super::{Period, PriceHistory, Ruin, RuinCause};
+
+/// How much is taken out at the start of every year of retirement.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WithdrawalRule {
+    /// The same amount every year, set as a share of the starting balance.
+    Fixed { rate: Percent },
+    /// A share of whatever the balance is at each withdrawal.
+    PercentOfPortfolio { rate: Percent },
+    /// Guyton–Klinger style guardrails: starts at `rate` of the starting balance, and
+    /// whenever the withdrawal drifts more than `band` (relative) from `rate` of the current
+    /// balance it's cut or raised by `adjustment`.
+    Guardrails {
+        rate: Percent,
+        band: Percent,
+        adjustment: Percent,
+    },
+}
+
+impl WithdrawalRule {
+    fn first_withdrawal(&self, starting_balance: f64) -> f64 {
+        match self {
+            WithdrawalRule::Fixed { rate }
+            | WithdrawalRule::PercentOfPortfolio { rate }
+            | WithdrawalRule::Guardrails { rate, .. } => starting_balance * rate.as_decimal(),
+        }
+    }
+
+    fn next_withdrawal(&self, previous: f64, balance: f64) -> f64 {
+        match self {
+            WithdrawalRule::Fixed { .. } => previous,
+            WithdrawalRule::PercentOfPortfolio { rate } => balance * rate.as_decimal(),
+            WithdrawalRule::Guardrails {
+                rate,
+                band,
+                adjustment,
+            } => {
+                let current_rate = previous / balance;
+                if current_rate > rate.as_decimal() * (1.0 + band.as_decimal()) {
+                    previous * (1.0 - adjustment.as_decimal())
+                } else if current_rate < rate.as_decimal() * (1.0 - band.as_decimal()) {
+                    previous * (1.0 + adjustment.as_decimal())
+                } else {
+                    previous
+                }
+            }
+        }
+    }
+}
+
+/// Living off a balance over a path: withdrawals are taken at the start of each year,
+/// counting the path's days in market years, and the rest stays invested.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Retirement {
+    starting_balance: f64,
+    rule: WithdrawalRule,
+}
+
+/// How a path's retirement went.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetirementOutcome {
+    terminal_wealth: f64,
+    depleted: bool,
+    safe_withdrawal_rate: Percent,
+}
+
+impl RetirementOutcome {
+    pub fn terminal_wealth(&self) -> f64 {
+        self.terminal_wealth
+    }
+
+    /// Whether a withdrawal couldn't be made in full.
+    pub fn depleted(&self) -> bool {
+        self.depleted
+    }
+
+    /// The most a fixed yearly withdrawal, as a share of the starting balance, could have
+    /// been on this path without running out.
+    pub fn safe_withdrawal_rate(&self) -> Percent {
+        self.safe_withdrawal_rate
+    }
+}
+
+impl Retirement {
+    pub fn new(starting_balance: f64, rule: WithdrawalRule) -> Self {
+        if starting_balance <= 0.0 {
+            panic!(
+                "Retirement needs a positive balance, got {}",
+                starting_balance
+            );
+        }
+
+        Self {
+            starting_balance,
+            rule,
+        }
+    }
+
+    pub fn starting_balance(&self) -> f64 {
+        self.starting_balance
+    }
+
+    pub fn rule(&self) -> WithdrawalRule {
+        self.rule
+    }
+
+    /// Once the path is ruined whatever is left sits in cash, the same as with cash flows.
+    pub fn apply(&self, price_history: &PriceHistory, ruin: Option<Ruin>) -> RetirementOutcome {
+        let yearly_growth = Self::yearly_growth(price_history, ruin);
+
+        let mut balance = self.starting_balance;
+        let mut withdrawal = self.rule.first_withdrawal(self.starting_balance);
+        let mut depleted = false;
+        for (year, growth) in yearly_growth.iter().enumerate() {
+            if year > 0 && balance > 0.0 {
+                withdrawal = self.rule.next_withdrawal(withdrawal, balance);
+            }
+            depleted |= balance < withdrawal;
+            balance = f64::max(balance - withdrawal, 0.0) * growth;
+        }
+
+        RetirementOutcome {
+            terminal_wealth: balance,
+            depleted,
+            safe_withdrawal_rate: Self::safe_withdrawal_rate(&yearly_growth),
+        }
+    }
+
+    fn yearly_growth(price_history: &PriceHistory, ruin: Option<Ruin>) -> Vec<f64> {
+        let days_per_year = Period::MARKET_DAYS_PER_YEAR as usize;
+        let days = price_history.iter().len();
+        let mut yearly_growth = Vec::with_capacity(days.div_ceil(days_per_year));
+        let mut growth = 1.0;
+        for (day, price_change) in price_history.iter().enumerate() {
+            if ruin.is_none_or(|ruin| day <= ruin.day()) {
+                growth *= price_change.percent_change().as_multiplier();
+            }
+            if ruin.is_some_and(|ruin| ruin.day() == day && ruin.cause() == RuinCause::TotalLoss) {
+                growth = 0.0;
+            }
+            if (day + 1) % days_per_year == 0 || day + 1 == days {
+                yearly_growth.push(growth);
+                // Nothing grows back from a total loss
+                growth = match growth > 0.0 {
+                    true => 1.0,
+                    false => 0.0,
+                };
+            }
+        }
+        yearly_growth
+    }
+
+    /// With a starting balance of 1, the balance at the start of year `t` is `a - W c` for
+    /// the growth `a` compounded since the start and `c` the growth of a unit withdrawn
+    /// each year since. Every withdrawal is made in full while `W <= a / (1 + c)`.
+    fn safe_withdrawal_rate(yearly_growth: &[f64]) -> Percent {
+        let (mut compounded, mut withdrawn) = (1.0, 0.0);
+        let mut safe_rate = f64::INFINITY;
+        for growth in yearly_growth {
+            safe_rate = f64::min(safe_rate, compounded / (1.0 + withdrawn));
+            compounded *= growth;
+            withdrawn = (withdrawn + 1.0) * growth;
+        }
+        Percent::from_decimal(safe_rate)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pricing::PriceChange;
+
+    use super::*;
+
+    #[test]
+    fn test_withdrawal_rules() {
+        // Doubles over the first year and is flat over the second
+        let days_per_year = Period::MARKET_DAYS_PER_YEAR as usize;
+        let price_history = std::iter::once(PriceChange::from(Percent::from_percent(100.0)))
+            .chain(std::iter::repeat_n(
+                PriceChange::zero(),
+                2 * days_per_year - 1,
+            ))
+            .collect::<PriceHistory>();
+        let outcome = |rule| Retirement::new(100.0, rule).apply(&price_history, None);
+
+        // Withdrawing 2/3 leaves 1/3 to double into exactly the second withdrawal
+        let fixed = outcome(WithdrawalRule::Fixed {
+            rate: Percent::from_percent(50.0),
+        });
+        assert!((fixed.safe_withdrawal_rate().as_decimal() - 2.0 / 3.0).abs() < 1e-12);
+        assert!((fixed.terminal_wealth() - 50.0).abs() < 1e-9);
+        assert!(!fixed.depleted());
+
+        let too_much = outcome(WithdrawalRule::Fixed {
+            rate: Percent::from_percent(70.0),
+        });
+        assert!(too_much.depleted());
+        assert_eq!(too_much.terminal_wealth(), 0.0);
+
+        // 50 of 100, then half of the 100 it grows back to
+        let percent = outcome(WithdrawalRule::PercentOfPortfolio {
+            rate: Percent::from_percent(50.0),
+        });
+        assert!((percent.terminal_wealth() - 50.0).abs() < 1e-9);
+
+        // 20 of 100 leaves 160, and 20 of 160 is under the 16% guardrail so it's raised to 30
+        let guardrails = outcome(WithdrawalRule::Guardrails {
+            rate: Percent::from_percent(20.0),
+            band: Percent::from_percent(20.0),
+            adjustment: Percent::from_percent(50.0),
+        });
+        assert!((guardrails.terminal_wealth() - 130.0).abs() < 1e-9);
+    }
+}
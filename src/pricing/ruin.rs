@@ -0,0 +1,131 @@
+use crate::number::Percent;
+
+use super::{PriceChange, PriceHistory};
+
+/// A rule under which a fund is shut down and liquidated at that day's NAV.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    /// NAV falls this far below its highest close.
+    Drawdown(Percent),
+    /// NAV falls this far in a single day.
+    DailyLoss(Percent),
+}
+
+impl Termination {
+    fn is_triggered(&self, nav: f64, peak_nav: f64, daily_multiplier: f64) -> bool {
+        match self {
+            Termination::Drawdown(drawdown) => nav <= peak_nav * (-*drawdown).as_multiplier(),
+            Termination::DailyLoss(loss) => daily_multiplier <= (-*loss).as_multiplier(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuinCause {
+    /// Everything was lost.
+    TotalLoss,
+    /// A termination rule closed the position.
+    Terminated,
+}
+
+/// When and how a path's position came to an end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ruin {
+    day: usize,
+    cause: RuinCause,
+}
+
+impl Ruin {
+    pub fn day(&self) -> usize {
+        self.day
+    }
+
+    pub fn cause(&self) -> RuinCause {
+        self.cause
+    }
+}
+
+/// Compounds a path day by day, stopping at the first day the position is wiped out or
+/// any of the terminations is triggered. A day losing more than everything ends the path
+/// at a total loss rather than compounding on from a negative NAV.
+pub fn track_ruin(
+    price_history: &PriceHistory,
+    terminations: &[Termination],
+) -> (PriceChange, Option<Ruin>) {
+    let mut nav = 1.0;
+    let mut peak_nav = 1.0f64;
+
+    for (day, price_change) in price_history.iter().enumerate() {
+        let multiplier = price_change.percent_change().as_multiplier();
+        if multiplier <= 0.0 {
+            let ruin = Ruin {
+                day,
+                cause: RuinCause::TotalLoss,
+            };
+            return (PriceChange::total_loss(), Some(ruin));
+        }
+
+        nav *= multiplier;
+        peak_nav = peak_nav.max(nav);
+        if terminations
+            .iter()
+            .any(|termination| termination.is_triggered(nav, peak_nav, multiplier))
+        {
+            let ruin = Ruin {
+                day,
+                cause: RuinCause::Terminated,
+            };
+            return (Percent::from_multiplier(nav).into(), Some(ruin));
+        }
+    }
+
+    (Percent::from_multiplier(nav).into(), None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_track_ruin() {
+        let price_history: PriceHistory = [10.0, -120.0, -150.0]
+            .iter()
+            .map(|&change| PriceChange::from(Percent::from_percent(change)))
+            .collect();
+
+        // Compounding straight through would turn two losses of everything into a gain
+        let (total, ruin) = track_ruin(&price_history, &[]);
+        assert_eq!(total, PriceChange::total_loss());
+        assert_eq!(
+            ruin.map(|ruin| (ruin.day(), ruin.cause())),
+            Some((1, RuinCause::TotalLoss))
+        );
+
+        let price_history: PriceHistory = [20.0, -30.0, -30.0, 50.0]
+            .iter()
+            .map(|&change| PriceChange::from(Percent::from_percent(change)))
+            .collect();
+        let (total, ruin) = track_ruin(
+            &price_history,
+            &[Termination::Drawdown(Percent::from_percent(50.0))],
+        );
+        assert!((total.percent_change().as_decimal() - (1.2 * 0.7 * 0.7 - 1.0)).abs() < 1e-12);
+        assert_eq!(
+            ruin.map(|ruin| (ruin.day(), ruin.cause())),
+            Some((2, RuinCause::Terminated))
+        );
+
+        // Whichever termination triggers first ends the path
+        let (_, ruin) = track_ruin(
+            &price_history,
+            &[
+                Termination::Drawdown(Percent::from_percent(50.0)),
+                Termination::DailyLoss(Percent::from_percent(25.0)),
+            ],
+        );
+        assert_eq!(
+            ruin.map(|ruin| (ruin.day(), ruin.cause())),
+            Some((1, RuinCause::Terminated))
+        );
+    }
+}
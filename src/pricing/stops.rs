@@ -0,0 +1,272 @@
+use std::fmt::Display;
+
+use crate::number::Percent;
+
+use super::{
+    leverage_mode::PathTradingCosts, Activity, CostDrag, Period, PriceChange, PriceHistory,
+};
+
+/// When a stop takes the position out of the market.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StopTrigger {
+    /// The position falls this far below where it was last entered.
+    Loss(Percent),
+    /// The position falls this far below its highest close since it was last entered.
+    Trailing(Percent),
+}
+
+/// When a stopped-out position goes back into the market.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Reentry {
+    /// The position, had it been held, recovers this far off its lowest close since the exit.
+    Recovery(Percent),
+    /// A fixed time after the exit.
+    After(Period),
+}
+
+/// A risk control laid over a variant's path: the position is sold to cash at the close of
+/// the day the stop triggers, earning the path's cash rates if there are any, and bought
+/// back at the close of the day it re-enters. Once back in, the stop isn't checked again
+/// until `cool_down` has passed. Each exit and re-entry trades the whole position and is
+/// charged the variant's trading costs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StopRule {
+    trigger: StopTrigger,
+    reentry: Reentry,
+    cool_down: Period,
+}
+
+/// What a stop did over a path.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StopOutcome {
+    triggers: usize,
+    days_out: usize,
+    days: usize,
+    // Share of equity left after buying back higher than each exit and paying for the
+    // trades, compounded
+    whipsaw_kept: f64,
+    unstopped_price_change: PriceChange,
+}
+
+impl StopOutcome {
+    /// How many times the stop took the position out.
+    pub fn triggers(&self) -> usize {
+        self.triggers
+    }
+
+    /// Share of the path's days spent in cash.
+    pub fn time_out(&self) -> Percent {
+        Percent::from_decimal(self.days_out as f64 / usize::max(self.days, 1) as f64)
+    }
+
+    /// The share of ending equity lost to round trips that bought back in higher than they
+    /// sold, net of the cash interest earned while out, and to the trading costs of the
+    /// stop's exits and re-entries.
+    pub fn whipsaw_cost(&self) -> Percent {
+        Percent::from_decimal(1.0 - self.whipsaw_kept)
+    }
+
+    /// The variant's total price change had it never been stopped out.
+    pub fn unstopped_price_change(&self) -> PriceChange {
+        self.unstopped_price_change
+    }
+}
+
+impl StopRule {
+    pub fn new(trigger: StopTrigger, reentry: Reentry, cool_down: Period) -> Self {
+        Self {
+            trigger,
+            reentry,
+            cool_down,
+        }
+    }
+
+    /// The path's daily changes with the stop in place, and what its holder sold and was
+    /// paid once the stop's own trades replace `activity` while out, along with `cost_drag`
+    /// with the stop's trades added and what the stop did.
+    pub fn apply(
+        &self,
+        price_history: &PriceHistory,
+        activity: &Activity,
+        mut cost_drag: CostDrag,
+        trading_costs: &PathTradingCosts,
+        cash_rates: Option<&[Percent]>,
+    ) -> (PriceHistory, Activity, CostDrag, StopOutcome) {
+        let mut invested = true;
+        // The position's value had it never been stopped out
+        let mut held = 1.0;
+        // Where the position was entered, or its peak since for a trailing stop
+        let mut reference = 1.0f64;
+        let mut checked_from = 0;
+        let (mut exit_day, mut exit_value, mut low, mut cash_growth) = (0, 1.0, 1.0f64, 1.0);
+        let (mut triggers, mut days_out, mut whipsaw_kept) = (0, 0, 1.0);
+        let mut stopped_activity = Activity::none();
+        // The stopped position's value, which the stop's trades are charged against
+        let mut equity = 1.0;
+        // Charges a trade of the whole position at the close of `day`, returning the share
+        // of equity kept
+        let mut trade = |day: usize, value: f64| {
+            let cost = f64::min(trading_costs.cost(day, value), value);
+            if cost > 0.0 {
+                cost_drag.record(cost, value);
+                1.0 - cost / value
+            } else {
+                1.0
+            }
+        };
+
+        let stopped = price_history
+            .iter()
+            .enumerate()
+            .map(|(day, &price_change)| {
+                held *= price_change.percent_change().as_multiplier();
+                if invested {
+                    stopped_activity.record_dividend(day, activity.dividend(day));
+                    if let StopTrigger::Trailing(_) = self.trigger {
+                        reference = reference.max(held);
+                    }
+                    let loss = match self.trigger {
+                        StopTrigger::Loss(loss) | StopTrigger::Trailing(loss) => loss,
+                    };
+                    if day >= checked_from && held <= reference * (-loss).as_multiplier() {
+                        invested = false;
+                        triggers += 1;
+                        (exit_day, exit_value, low, cash_growth) = (day, held, held, 1.0);
+                        stopped_activity.record_sale(day, 1.0);
+                        let multiplier = price_change.percent_change().as_multiplier();
+                        let kept = trade(day, equity * multiplier);
+                        whipsaw_kept *= kept;
+                        equity *= multiplier * kept;
+                        return Percent::from_multiplier(multiplier * kept).into();
+                    }
+                    stopped_activity.record_sale(day, activity.sold(day));
+                    equity *= price_change.percent_change().as_multiplier();
+                    return price_change;
+                }
+
+                let cash_rate = cash_rates.map_or(Percent::zero(), |cash_rates| cash_rates[day]);
+                cash_growth *= cash_rate.as_multiplier();
+                days_out += 1;
+                low = low.min(held);
+                let reenters = match self.reentry {
+                    // Nothing recovers from a total loss
+                    Reentry::Recovery(recovery) => {
+                        held > 0.0 && held >= low * recovery.as_multiplier()
+                    }
+                    Reentry::After(period) => (day - exit_day) as u64 >= period.as_days(),
+                };
+                if reenters {
+                    invested = true;
+                    reference = held;
+                    checked_from = day + 1 + self.cool_down.as_days() as usize;
+                    let missed = held / exit_value;
+                    if missed > cash_growth {
+                        whipsaw_kept *= cash_growth / missed;
+                    }
+                    let kept = trade(day, equity * cash_rate.as_multiplier());
+                    whipsaw_kept *= kept;
+                    equity *= cash_rate.as_multiplier() * kept;
+                    return Percent::from_multiplier(cash_rate.as_multiplier() * kept).into();
+                }
+                equity *= cash_rate.as_multiplier();
+                cash_rate.into()
+            })
+            .collect();
+
+        let outcome = StopOutcome {
+            triggers,
+            days_out,
+            days: price_history.iter().len(),
+            whipsaw_kept,
+            unstopped_price_change: Percent::from_multiplier(f64::max(held, 0.0)).into(),
+        };
+        (stopped, stopped_activity, cost_drag, outcome)
+    }
+}
+
+impl Display for StopRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let trigger = match self.trigger {
+            StopTrigger::Loss(loss) => format!("stop {:.0}", loss),
+            StopTrigger::Trailing(loss) => format!("trail {:.0}", loss),
+        };
+        let reentry = match self.reentry {
+            Reentry::Recovery(recovery) => format!("back +{:.0}", recovery),
+            Reentry::After(period) => format!("back {}d", period.as_days()),
+        };
+        f.pad(&format!("{} {}", trigger, reentry))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pricing::TradingCosts;
+
+    use super::*;
+
+    #[test]
+    fn test_stops() {
+        let price_history: PriceHistory = [10.0, -20.0, -10.0, 20.0, 20.0, 10.0, -40.0]
+            .iter()
+            .map(|&change| PriceChange::from(Percent::from_percent(change)))
+            .collect();
+        let total = |stopped: &PriceHistory| stopped.total().percent_change().as_decimal();
+
+        // 1.1 falls to 0.88, 20% off its peak, and is out until it's recovered 10% off the 0.792 low
+        let trailing = StopRule::new(
+            StopTrigger::Trailing(Percent::from_percent(20.0)),
+            Reentry::Recovery(Percent::from_percent(10.0)),
+            Period::Days(0),
+        );
+        let (stopped, activity, cost_drag, outcome) = trailing.apply(
+            &price_history,
+            &Activity::none(),
+            CostDrag::none(),
+            &TradingCosts::None.along(&price_history),
+            None,
+        );
+        assert_eq!(outcome.triggers(), 2);
+        assert!((total(&stopped) - (0.88 * 1.2 * 1.1 * 0.6 - 1.0)).abs() < 1e-12);
+        // Each exit sells the whole position
+        assert_eq!(activity.sold(1), 1.0);
+        assert_eq!(activity.sold(6), 1.0);
+        // Sold at 0.88 and bought back at 0.9504
+        assert!((outcome.whipsaw_cost().as_decimal() - (1.0 - 1.0 / 1.08)).abs() < 1e-12);
+        let unstopped = outcome
+            .unstopped_price_change()
+            .percent_change()
+            .as_decimal();
+        assert!((unstopped - total(&price_history)).abs() < 1e-12);
+
+        assert_eq!(cost_drag.trades(), 0);
+
+        // Out for two days after a 10% loss from entry, earning 1% a day, and paying 1% to
+        // sell and 1% to buy back. The cool-down keeps the last day's loss from triggering it
+        // again.
+        let timed = StopRule::new(
+            StopTrigger::Loss(Percent::from_percent(10.0)),
+            Reentry::After(Period::Days(2)),
+            Period::Days(5),
+        );
+        let cash_rates = vec![Percent::from_percent(1.0); 7];
+        let trading_costs = TradingCosts::Notional {
+            rate: Percent::from_percent(1.0),
+        };
+        let (stopped, _, cost_drag, outcome) = timed.apply(
+            &price_history,
+            &Activity::none(),
+            CostDrag::none(),
+            &trading_costs.along(&price_history),
+            Some(&cash_rates),
+        );
+        assert_eq!(outcome.triggers(), 1);
+        let round_trip = 0.99 * 0.99;
+        let expected = 0.88 * 1.0201 * round_trip * 1.2 * 1.1 * 0.6 - 1.0;
+        assert!((total(&stopped) - expected).abs() < 1e-12);
+        let whipsaw_cost = 1.0 - 1.0201 / 1.08 * round_trip;
+        assert!((outcome.whipsaw_cost().as_decimal() - whipsaw_cost).abs() < 1e-12);
+        assert_eq!(cost_drag.trades(), 2);
+        assert!((cost_drag.drag().as_decimal() - (1.0 - round_trip)).abs() < 1e-12);
+        assert!((outcome.time_out().as_decimal() - 2.0 / 7.0).abs() < 1e-12);
+    }
+}
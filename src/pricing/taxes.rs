@@ -0,0 +1,344 @@
+use std::collections::VecDeque;
+
+use crate::number::Percent;
+
+use super::{Activity, CashFlows, Period, PriceChange, PriceHistory, Ruin, RuinCause};
+
+/// Rates charged on realized gains, depending on how long the lot was held, and on
+/// distributions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TaxRates {
+    short_term: Percent,
+    long_term: Percent,
+    dividend: Percent,
+}
+
+impl TaxRates {
+    pub fn new(short_term: Percent, long_term: Percent, dividend: Percent) -> Self {
+        Self {
+            short_term,
+            long_term,
+            dividend,
+        }
+    }
+
+    /// Charged on gains from lots held for less than a year, and on everything withdrawn
+    /// from a tax-deferred account.
+    pub fn short_term(&self) -> Percent {
+        self.short_term
+    }
+
+    pub fn long_term(&self) -> Percent {
+        self.long_term
+    }
+
+    pub fn dividend(&self) -> Percent {
+        self.dividend
+    }
+}
+
+/// Holding a variant in a taxable account. Whatever the variant's mode sells is sold and
+/// bought back in the account, and the dividends it's paid are taxed and reinvested.
+/// Gains are realized first in, first out, and each year's tax is paid by selling at its
+/// end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TaxableAccount {
+    rates: TaxRates,
+}
+
+/// What a path's balance came to once taxed, next to the same flows in a tax-deferred
+/// account.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TaxOutcome {
+    after_tax_wealth: f64,
+    taxes_paid: f64,
+    tax_deferred_wealth: f64,
+}
+
+impl TaxOutcome {
+    /// The taxable balance once everything left is sold and the last taxes are paid.
+    pub fn after_tax_wealth(&self) -> f64 {
+        self.after_tax_wealth
+    }
+
+    /// Every tax paid along the path, summed as it was paid.
+    pub fn taxes_paid(&self) -> f64 {
+        self.taxes_paid
+    }
+
+    /// The balance of an account that's never taxed along the way, after withdrawing it
+    /// all. Contributions are taken as already taxed, like a non-deductible IRA, so only
+    /// the gains are taxed, and as ordinary income, which the short-term rate stands in for.
+    pub fn tax_deferred_wealth(&self) -> f64 {
+        self.tax_deferred_wealth
+    }
+
+    /// The same outcome in the money of the path's start.
+    pub fn deflate(&self, inflation: PriceChange) -> Self {
+        let multiplier = inflation.percent_change().as_multiplier();
+        Self {
+            after_tax_wealth: self.after_tax_wealth / multiplier,
+            taxes_paid: self.taxes_paid,
+            tax_deferred_wealth: self.tax_deferred_wealth / multiplier,
+        }
+    }
+}
+
+impl TaxableAccount {
+    pub fn new(rates: TaxRates) -> Self {
+        Self { rates }
+    }
+
+    pub fn rates(&self) -> TaxRates {
+        self.rates
+    }
+
+    /// Runs the account along a path's daily changes with the same flows as `cash_flows`,
+    /// selling and being paid what `activity` says the path's mode did. Contributions buy
+    /// new lots and withdrawals sell the oldest first. Once the path is ruined whatever is
+    /// left sits in cash, the same as with cash flows.
+    pub fn apply(
+        &self,
+        price_history: &PriceHistory,
+        activity: &Activity,
+        ruin: Option<Ruin>,
+        cash_flows: &CashFlows,
+    ) -> TaxOutcome {
+        let days = price_history.iter().len();
+        let days_per_year = Period::MARKET_DAYS_PER_YEAR as usize;
+        let flow_interval = cash_flows.interval().as_days() as usize;
+
+        let mut lots = Lots::default();
+        let mut year = TaxYear::default();
+        lots.buy(cash_flows.initial_balance(), 0);
+        let (mut carried_loss, mut taxes_paid) = (0.0, 0.0);
+        // The same flows with nothing taxed until the end
+        let mut deferred = cash_flows.initial_balance();
+        let mut contributed = cash_flows.initial_balance();
+        let mut after_tax_wealth = cash_flows.initial_balance();
+
+        // Lots are dated by the close they're bought or sold at
+        for (day, price_change) in price_history.iter().enumerate() {
+            let close = day + 1;
+            let invested = ruin.is_none_or(|ruin| day <= ruin.day());
+            if invested {
+                let multiplier = price_change.percent_change().as_multiplier();
+                lots.grow(multiplier);
+                deferred *= multiplier;
+            }
+            if ruin.is_some_and(|ruin| ruin.day() == day && ruin.cause() == RuinCause::TotalLoss) {
+                lots.sell_all(close, &mut year);
+                deferred = 0.0;
+            }
+
+            if invested {
+                let distribution = lots.distribute(activity.dividend(day));
+                year.dividends += distribution;
+                lots.buy(distribution, close);
+
+                let sold = activity.sold(day);
+                if sold > 0.0 {
+                    let sold = lots.sell(sold * lots.value(), close, &mut year);
+                    lots.buy(sold, close);
+                }
+            }
+            if close % flow_interval == 0 {
+                let amount = cash_flows.amount();
+                match amount >= 0.0 {
+                    true => lots.buy(amount, close),
+                    false => {
+                        lots.sell(-amount, close, &mut year);
+                    }
+                }
+                deferred = f64::max(deferred + amount, 0.0);
+                contributed = f64::max(contributed + amount, 0.0);
+            }
+
+            if close == days {
+                let value = lots.value();
+                lots.sell_all(close, &mut year);
+                let tax = year.settle(&self.rates, &mut carried_loss);
+                taxes_paid += tax;
+                after_tax_wealth = f64::max(value - tax, 0.0);
+            } else if close % days_per_year == 0 {
+                let tax = year.settle(&self.rates, &mut carried_loss);
+                // Selling to pay is itself taxed the year after
+                taxes_paid += lots.sell(tax, close, &mut year);
+            }
+        }
+
+        // Withdrawals are taken out of what was contributed first
+        let deferred_gains = f64::max(deferred - contributed, 0.0);
+        TaxOutcome {
+            after_tax_wealth,
+            taxes_paid,
+            tax_deferred_wealth: deferred - deferred_gains * self.rates.short_term.as_decimal(),
+        }
+    }
+}
+
+/// Realized gains and distributions received over the current tax year.
+#[derive(Debug, Clone, Copy, Default)]
+struct TaxYear {
+    short_term_gains: f64,
+    long_term_gains: f64,
+    dividends: f64,
+}
+
+impl TaxYear {
+    /// The tax due on the year, netting losses of either term against gains of the other
+    /// and carrying what's left over into the next year. Starts a new year.
+    fn settle(&mut self, rates: &TaxRates, carried_loss: &mut f64) -> f64 {
+        let mut short_term = self.short_term_gains;
+        let mut long_term = self.long_term_gains - *carried_loss;
+        if short_term < 0.0 && long_term > 0.0 {
+            long_term += short_term;
+            short_term = 0.0;
+        } else if long_term < 0.0 && short_term > 0.0 {
+            short_term += long_term;
+            long_term = 0.0;
+        }
+        *carried_loss = -(f64::min(short_term, 0.0) + f64::min(long_term, 0.0));
+
+        let tax = f64::max(short_term, 0.0) * rates.short_term.as_decimal()
+            + f64::max(long_term, 0.0) * rates.long_term.as_decimal()
+            + self.dividends * rates.dividend.as_decimal();
+        *self = TaxYear::default();
+        tax
+    }
+}
+
+/// Shares bought at different times and prices, oldest first.
+#[derive(Debug, Clone, Default)]
+struct Lots {
+    lots: VecDeque<Lot>,
+    // Across every lot, so the value doesn't take a pass over them
+    units: f64,
+    price: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Lot {
+    units: f64,
+    cost: f64,
+    day: usize,
+}
+
+impl Lots {
+    fn value(&self) -> f64 {
+        self.units * self.price
+    }
+
+    fn grow(&mut self, multiplier: f64) {
+        self.price *= multiplier;
+    }
+
+    fn buy(&mut self, amount: f64, day: usize) {
+        if self.lots.is_empty() {
+            // A fresh start after everything was sold, or lost
+            self.price = 1.0;
+        }
+        if amount > 0.0 {
+            let units = amount / self.price;
+            self.units += units;
+            self.lots.push_back(Lot {
+                units,
+                cost: amount,
+                day,
+            });
+        }
+    }
+
+    /// Pays out a share of every unit's value, returning the total paid.
+    fn distribute(&mut self, share: f64) -> f64 {
+        let distribution = self.value() * share;
+        self.price *= 1.0 - share;
+        distribution
+    }
+
+    /// Sells up to `amount`, oldest lots first, returning how much was sold.
+    fn sell(&mut self, amount: f64, day: usize, year: &mut TaxYear) -> f64 {
+        let days_per_year = Period::MARKET_DAYS_PER_YEAR as usize;
+        let mut units_left = match self.price > 0.0 {
+            true => amount / self.price,
+            false => f64::INFINITY,
+        };
+        let mut sold = 0.0;
+        while units_left > 0.0 {
+            let Some(lot) = self.lots.front_mut() else {
+                break;
+            };
+            let units = f64::min(lot.units, units_left);
+            let cost = lot.cost * units / lot.units;
+            let gain = units * self.price - cost;
+            match day - lot.day >= days_per_year {
+                true => year.long_term_gains += gain,
+                false => year.short_term_gains += gain,
+            }
+            sold += units * self.price;
+            units_left -= units;
+            if units == lot.units {
+                self.lots.pop_front();
+                self.units = match self.lots.is_empty() {
+                    true => 0.0,
+                    false => self.units - units,
+                };
+            } else {
+                self.units -= units;
+                lot.units -= units;
+                lot.cost -= cost;
+            }
+        }
+        sold
+    }
+
+    fn sell_all(&mut self, day: usize, year: &mut TaxYear) {
+        self.sell(f64::INFINITY, day, year);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_taxable_account() {
+        let rates = TaxRates::new(
+            Percent::from_percent(40.0),
+            Percent::from_percent(20.0),
+            Percent::from_percent(30.0),
+        );
+        let days_per_year = Period::MARKET_DAYS_PER_YEAR as usize;
+        // Doubles on the first day and holds for two years
+        let price_history = std::iter::once(PriceChange::from(Percent::from_percent(100.0)))
+            .chain(std::iter::repeat_n(
+                PriceChange::zero(),
+                2 * days_per_year - 1,
+            ))
+            .collect::<PriceHistory>();
+        let lump_sum = CashFlows::new(100.0, 0.0, Period::Years(10));
+
+        let account = TaxableAccount::new(rates);
+
+        // Held throughout, the 100 gain is long-term when it's sold
+        let buy_and_hold = account.apply(&price_history, &Activity::none(), None, &lump_sum);
+        assert!((buy_and_hold.after_tax_wealth() - 180.0).abs() < 1e-9);
+        assert!((buy_and_hold.taxes_paid() - 20.0).abs() < 1e-9);
+        assert!((buy_and_hold.tax_deferred_wealth() - 160.0).abs() < 1e-9);
+
+        // Selling everything after half a year makes the gain short-term, taxed that year
+        let mut sold_off = Activity::none();
+        sold_off.record_sale(days_per_year / 2 - 1, 1.0);
+        let turned_over = account.apply(&price_history, &sold_off, None, &lump_sum);
+        assert!((turned_over.taxes_paid() - 40.0).abs() < 1e-9);
+        assert!((turned_over.after_tax_wealth() - 160.0).abs() < 1e-9);
+
+        // A 10% distribution at the end of the first year is taxed as a dividend, and what's
+        // sold to pay for it comes out of the oldest lot, at a long-term gain
+        let mut paid = Activity::none();
+        paid.record_dividend(days_per_year - 1, 0.1);
+        let distributing = account.apply(&price_history, &paid, None, &lump_sum);
+        assert!((distributing.taxes_paid() - 22.0).abs() < 1e-9);
+        assert!((distributing.after_tax_wealth() - 178.0).abs() < 1e-9);
+    }
+}
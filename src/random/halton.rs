@@ -0,0 +1,156 @@
+use std::sync::Arc;
+
+use super::{PseudoRandomSequence, PseudoRandomSource, UniformSequence, UniformSource};
+
+/// A scrambled Halton sequence. Each path is one point of the sequence and each draw the
+/// path makes is one coordinate of that point, so draw `j` on day `d` of path `n` maps to
+/// coordinate `d * draws_per_day + j` of point `n`.
+///
+/// Every digit of every coordinate goes through a random linear scramble (`a * d + b` mod
+/// the base) derived from the seed. This keeps the low discrepancy of the sequence while
+/// breaking up the correlation between the high dimensions, which would otherwise bias
+/// results over paths with thousands of days. Draws beyond the configured dimensions fall
+/// back to pseudo-random numbers.
+///
+/// Points of the sequence are not independent, so standard errors computed as if they were
+/// overstate the error. Comparing runs with different seeds gives the real one.
+#[derive(Debug, Clone)]
+pub struct HaltonSequence {
+    seed: u64,
+    bases: Arc<Vec<u64>>,
+    padding: PseudoRandomSequence,
+}
+
+impl HaltonSequence {
+    pub fn new(seed: u64, dimensions: usize) -> Self {
+        Self {
+            seed,
+            bases: Arc::new(first_primes(dimensions)),
+            padding: PseudoRandomSequence::new(seed),
+        }
+    }
+
+    pub fn dimensions(&self) -> usize {
+        self.bases.len()
+    }
+
+    /// The coordinate of the point for a path, in the open interval (0, 1).
+    pub fn coordinate(&self, path: u64, dimension: usize) -> f64 {
+        let base = self.bases[dimension];
+        let inverse_base = 1.0 / base as f64;
+
+        // Skip the origin, which is the first point of every unscrambled dimension
+        let mut index = path + 1;
+        let mut factor = inverse_base;
+        let mut digit_position = 0;
+        let mut coordinate = 0.0;
+
+        // The scramble applies to the leading zero digits too, so keep going until the
+        // remaining digits can no longer change the result
+        while index > 0 || factor > f64::EPSILON {
+            let digit = index % base;
+            let hash = mix(self.seed ^ mix(dimension as u64 ^ mix(digit_position)));
+            let multiplier = 1 + (hash >> 32) % (base - 1);
+            let shift = (hash & 0xFFFF_FFFF) % base;
+            coordinate += ((multiplier * digit + shift) % base) as f64 * factor;
+
+            index /= base;
+            factor *= inverse_base;
+            digit_position += 1;
+        }
+
+        coordinate.clamp(f64::MIN_POSITIVE, 1.0 - f64::EPSILON)
+    }
+}
+
+impl UniformSequence for HaltonSequence {
+    type Source = HaltonSource;
+
+    fn path_source(&self, path: u64) -> Self::Source {
+        HaltonSource {
+            sequence: self.clone(),
+            path,
+            dimension: 0,
+            padding: self.padding.path_source(path),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HaltonSource {
+    sequence: HaltonSequence,
+    path: u64,
+    dimension: usize,
+    padding: PseudoRandomSource,
+}
+
+impl UniformSource for HaltonSource {
+    fn next_uniform(&mut self) -> f64 {
+        let dimension = self.dimension;
+        self.dimension += 1;
+
+        if dimension < self.sequence.dimensions() {
+            self.sequence.coordinate(self.path, dimension)
+        } else {
+            self.padding.next_uniform()
+        }
+    }
+}
+
+// SplitMix64 finalizer, used to derive independent digit shifts from the seed
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn first_primes(count: usize) -> Vec<u64> {
+    // The n-th prime is below n * (ln n + ln ln n) for n >= 6
+    let n = count.max(6) as f64;
+    let limit = (n * (n.ln() + n.ln().ln())).ceil() as usize + 1;
+
+    let mut composite = vec![false; limit + 1];
+    let mut primes = Vec::with_capacity(count);
+    for candidate in 2..=limit {
+        if primes.len() == count {
+            break;
+        }
+        if composite[candidate] {
+            continue;
+        }
+        primes.push(candidate as u64);
+        for multiple in (candidate * candidate..=limit).step_by(candidate) {
+            composite[multiple] = true;
+        }
+    }
+
+    primes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_first_primes() {
+        assert_eq!(first_primes(10), vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert_eq!(first_primes(10_000).last(), Some(&104_729));
+    }
+
+    #[test]
+    fn test_coordinates_are_stratified() {
+        let sequence = HaltonSequence::new(42, 3);
+
+        for (dimension, &base) in [2u64, 3, 5].iter().enumerate() {
+            let bins = base * base;
+            let mut hits = vec![0; bins as usize];
+            for path in 0..bins {
+                let coordinate = sequence.coordinate(path, dimension);
+                assert!(coordinate > 0.0 && coordinate < 1.0);
+                hits[(coordinate * bins as f64) as usize] += 1;
+            }
+            assert!(hits.iter().all(|&count| count == 1));
+        }
+    }
+}
@@ -0,0 +1,9 @@
+mod halton;
+mod normal;
+mod pareto;
+mod uniform;
+
+pub use halton::*;
+pub use normal::*;
+pub use pareto::*;
+pub use uniform::*;
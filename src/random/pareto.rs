@@ -0,0 +1,93 @@
+/// Generalized Pareto distribution of exceedances over a threshold, with
+/// `P(X > x) = (1 + shape * x / scale) ^ (-1 / shape)`.
+/// Fewer exceedances than this don't say enough about a tail to fit it.
+pub const MIN_EXCEEDANCES: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeneralizedPareto {
+    shape: f64,
+    scale: f64,
+}
+
+impl GeneralizedPareto {
+    pub fn new(shape: f64, scale: f64) -> Self {
+        if !scale.is_finite() || scale <= 0.0 || !shape.is_finite() {
+            panic!(
+                "Invalid generalized Pareto parameters: shape {}, scale {}",
+                shape, scale
+            );
+        }
+        Self { shape, scale }
+    }
+
+    /// Fits the distribution to exceedances with Hosking and Wallis' probability weighted
+    /// moments, which stay well behaved for the small samples found in a tail. Gives `None`
+    /// for fewer than `MIN_EXCEEDANCES` exceedances, or ones too degenerate to fit (e.g. all
+    /// the same).
+    pub fn fit(exceedances: &[f64]) -> Option<Self> {
+        if exceedances.len() < MIN_EXCEEDANCES {
+            return None;
+        }
+
+        let mut sorted = Vec::from(exceedances);
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let count = sorted.len() as f64;
+        let first_moment = sorted.iter().sum::<f64>() / count;
+        let second_moment = sorted
+            .iter()
+            .enumerate()
+            .map(|(i, exceedance)| (count - 1.0 - i as f64) / (count - 1.0) * exceedance)
+            .sum::<f64>()
+            / count;
+
+        let denominator = first_moment - 2.0 * second_moment;
+        let shape = 2.0 - first_moment / denominator;
+        let scale = 2.0 * first_moment * second_moment / denominator;
+        match scale.is_finite() && scale > 0.0 && shape.is_finite() {
+            true => Some(Self::new(shape, scale)),
+            false => None,
+        }
+    }
+
+    pub fn shape(&self) -> f64 {
+        self.shape
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// The exceedance that is surpassed with the given probability.
+    pub fn exceedance(&self, survival_probability: f64) -> f64 {
+        if self.shape.abs() < 1e-9 {
+            -self.scale * survival_probability.ln()
+        } else {
+            self.scale / self.shape * (survival_probability.powf(-self.shape) - 1.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fit_recovers_parameters() {
+        let expected = GeneralizedPareto::new(0.25, 0.02);
+        let exceedances: Vec<f64> = (0..2_000)
+            .map(|i| (i as f64 + 0.5) / 2_000.0)
+            .map(|survival_probability| expected.exceedance(survival_probability))
+            .collect();
+
+        let fitted = GeneralizedPareto::fit(&exceedances).unwrap();
+        assert!((fitted.shape() - expected.shape()).abs() < 0.02);
+        assert!((fitted.scale() - expected.scale()).abs() < 0.001);
+
+        assert_eq!(
+            GeneralizedPareto::fit(&exceedances[..MIN_EXCEEDANCES - 1]),
+            None
+        );
+        assert_eq!(GeneralizedPareto::fit(&[0.01; MIN_EXCEEDANCES]), None);
+    }
+}
@@ -0,0 +1,75 @@
+use std::fmt::Debug;
+
+use rand::{distributions::Open01, rngs::StdRng, Rng, SeedableRng};
+
+/// A stream of uniform draws in the open interval (0, 1) used to build a single path.
+pub trait UniformSource {
+    fn next_uniform(&mut self) -> f64;
+}
+
+/// Hands out the uniform source for each simulated path. Sources are derived from the
+/// path index alone, so the same path can be rebuilt (or mirrored) on any thread.
+pub trait UniformSequence: Debug + Send + Sync {
+    type Source: UniformSource;
+
+    fn path_source(&self, path: u64) -> Self::Source;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PseudoRandomSequence {
+    seed: u64,
+}
+
+impl PseudoRandomSequence {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+}
+
+impl UniformSequence for PseudoRandomSequence {
+    type Source = PseudoRandomSource;
+
+    fn path_source(&self, path: u64) -> Self::Source {
+        // seed_from_u64 scrambles its input, so neighbouring paths get unrelated streams
+        let path_seed = self.seed ^ path.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        PseudoRandomSource {
+            rng: StdRng::seed_from_u64(path_seed),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PseudoRandomSource {
+    rng: StdRng,
+}
+
+impl UniformSource for PseudoRandomSource {
+    fn next_uniform(&mut self) -> f64 {
+        self.rng.sample(Open01)
+    }
+}
+
+/// Reflects every draw of the wrapped source (`u` becomes `1 - u`). Feeding a path built
+/// from a source and one built from its mirror gives an antithetic pair.
+#[derive(Debug, Clone)]
+pub struct MirroredSource<S> {
+    source: S,
+}
+
+impl<S> MirroredSource<S>
+where
+    S: UniformSource,
+{
+    pub fn new(source: S) -> Self {
+        Self { source }
+    }
+}
+
+impl<S> UniformSource for MirroredSource<S>
+where
+    S: UniformSource,
+{
+    fn next_uniform(&mut self) -> f64 {
+        1.0 - self.source.next_uniform()
+    }
+}
@@ -0,0 +1,59 @@
+use crate::{
+    number::Percent,
+    pricing::{PriceChange, PriceHistoryDescriptor},
+};
+
+use super::PriceChangeStatisticValue;
+
+#[derive(Debug, Clone, Copy)]
+pub struct AveragePriceChange {
+    price_change_sum: f64,
+    annualized_price_change_sum: f64,
+    count: u64,
+}
+
+impl AveragePriceChange {
+    pub fn average(&self) -> PriceChange {
+        Percent::from_decimal(self.price_change_sum / (self.count as f64)).into()
+    }
+
+    pub fn annualized_average(&self) -> PriceChange {
+        Percent::from_decimal(self.annualized_price_change_sum / (self.count as f64)).into()
+    }
+}
+
+impl PriceChangeStatisticValue for AveragePriceChange {
+    type Context = ();
+
+    fn new(
+        price_change: PriceChange,
+        descriptor: &PriceHistoryDescriptor,
+        _context: Option<&Self::Context>,
+    ) -> Self {
+        Self {
+            price_change_sum: price_change.percent_change().as_decimal(),
+            annualized_price_change_sum: price_change
+                .annualized_return(descriptor.period())
+                .percent_change()
+                .as_decimal(),
+            count: 1,
+        }
+    }
+
+    fn identity() -> Self {
+        Self {
+            price_change_sum: 0.0,
+            annualized_price_change_sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn reduce(a: Self, b: Self, _context: Option<&Self::Context>) -> Self {
+        Self {
+            price_change_sum: a.price_change_sum + b.price_change_sum,
+            annualized_price_change_sum: a.annualized_price_change_sum
+                + b.annualized_price_change_sum,
+            count: a.count + b.count,
+        }
+    }
+}
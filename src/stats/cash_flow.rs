@@ -0,0 +1,112 @@
+use crate::{
+    number::Percent,
+    pricing::{PriceHistoryDescriptor, VariantOutcome},
+};
+
+use super::{PriceHistoryStatisticValue, UntrackedOutcome};
+
+/// The distribution across paths of ending balances and money-weighted returns once cash
+/// flows are applied.
+#[derive(Debug, Clone)]
+pub struct CashFlowDistribution {
+    ending_balances: Vec<f64>,
+    // Only the paths with a solvable return
+    money_weighted_returns: Vec<f64>,
+    depleted_count: u64,
+    count: u64,
+}
+
+impl CashFlowDistribution {
+    pub fn mean_ending_balance(&self) -> f64 {
+        self.ending_balances.iter().sum::<f64>() / self.count as f64
+    }
+
+    pub fn ending_balance_percentile(&self, percentile: Percent) -> f64 {
+        percentile_of(&self.ending_balances, percentile)
+    }
+
+    /// A percentile of the annualized money-weighted return, across the paths it could be
+    /// solved for.
+    pub fn money_weighted_return_percentile(&self, percentile: Percent) -> Option<Percent> {
+        match self.money_weighted_returns.is_empty() {
+            true => None,
+            false => Some(Percent::from_decimal(percentile_of(
+                &self.money_weighted_returns,
+                percentile,
+            ))),
+        }
+    }
+
+    /// Share of paths where withdrawals used up the whole balance.
+    pub fn depletion_probability(&self) -> Percent {
+        Percent::from_decimal(self.depleted_count as f64 / self.count as f64)
+    }
+}
+
+pub(super) fn percentile_of(sorted: &[f64], percentile: Percent) -> f64 {
+    let index = percentile.as_decimal() * sorted.len() as f64;
+    let index = usize::min(f64::floor(index) as usize, sorted.len() - 1);
+    sorted[index]
+}
+
+pub(super) fn merge_sorted(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut merged = Vec::with_capacity(a.len() + b.len());
+    let (mut cursor_a, mut cursor_b) = (0, 0);
+    while cursor_a < a.len() && cursor_b < b.len() {
+        if a[cursor_a] < b[cursor_b] {
+            merged.push(a[cursor_a]);
+            cursor_a += 1;
+        } else {
+            merged.push(b[cursor_b]);
+            cursor_b += 1;
+        }
+    }
+    merged.extend_from_slice(&a[cursor_a..]);
+    merged.extend_from_slice(&b[cursor_b..]);
+    merged
+}
+
+impl PriceHistoryStatisticValue for CashFlowDistribution {
+    type Context = ();
+
+    fn identity() -> Self {
+        Self {
+            ending_balances: Vec::new(),
+            money_weighted_returns: Vec::new(),
+            depleted_count: 0,
+            count: 0,
+        }
+    }
+
+    fn from_outcome(
+        outcome: &VariantOutcome,
+        _descriptor: &PriceHistoryDescriptor,
+        _context: Option<&Self::Context>,
+    ) -> Result<Self, UntrackedOutcome> {
+        let cash_flows = outcome
+            .cash_flows()
+            .ok_or(UntrackedOutcome::new("Cash flows"))?;
+        Ok(Self {
+            ending_balances: vec![cash_flows.ending_balance()],
+            money_weighted_returns: cash_flows
+                .money_weighted_return()
+                .map(|rate| rate.as_decimal())
+                .into_iter()
+                .collect(),
+            depleted_count: cash_flows.depleted() as u64,
+            count: 1,
+        })
+    }
+
+    fn reduce(a: Self, b: Self, _context: Option<&Self::Context>) -> Self {
+        Self {
+            ending_balances: merge_sorted(&a.ending_balances, &b.ending_balances),
+            money_weighted_returns: merge_sorted(
+                &a.money_weighted_returns,
+                &b.money_weighted_returns,
+            ),
+            depleted_count: a.depleted_count + b.depleted_count,
+            count: a.count + b.count,
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{
+    number::Percent,
+    pricing::{PriceChange, PriceHistoryDescriptor, VariantOutcome},
+    types::Predicate,
+};
+
+use super::{GroupedStatisticValue, PriceHistoryStatisticValue, UntrackedOutcome};
+
+/// Estimates the mean of some observable of each path's total price change, using the
+/// underlying (1x) price change of the same path as a control variate.
+///
+/// The underlying outcome's expectation is known, so the regression adjusted estimate
+/// `mean(y) - beta * (mean(x) - E[x])` removes the part of the sampling noise that the
+/// leveraged outcome shares with it.
+#[derive(Debug, Clone, Copy)]
+pub struct ControlVariateEstimate {
+    expected_control: f64,
+    control_mean: f64,
+    observed_mean: f64,
+    control_sum_of_squares: f64,
+    observed_sum_of_squares: f64,
+    sum_of_products: f64,
+    count: u64,
+}
+
+pub struct ControlVariateContext {
+    expected_control: f64,
+    observable: Box<dyn Fn(PriceChange) -> f64 + Send + Sync>,
+}
+
+impl ControlVariateContext {
+    /// Estimate the average total price change.
+    pub fn average(expected_control: PriceChange) -> Self {
+        Self {
+            expected_control: expected_control.percent_change().as_decimal(),
+            observable: Box::new(|price_change: PriceChange| {
+                price_change.percent_change().as_decimal()
+            }),
+        }
+    }
+
+    /// Estimate the probability that the total price change matches the predicate.
+    pub fn probability<F>(expected_control: PriceChange, predicate: F) -> Self
+    where
+        F: 'static + Predicate<PriceChange>,
+    {
+        Self {
+            expected_control: expected_control.percent_change().as_decimal(),
+            observable: Box::new(move |price_change| match predicate.test(price_change) {
+                true => 1.0,
+                false => 0.0,
+            }),
+        }
+    }
+}
+
+impl ControlVariateEstimate {
+    pub fn beta(&self) -> f64 {
+        if self.control_sum_of_squares > 0.0 {
+            self.sum_of_products / self.control_sum_of_squares
+        } else {
+            0.0
+        }
+    }
+
+    /// The plain Monte Carlo estimate, ignoring the control.
+    pub fn naive_estimate(&self) -> Percent {
+        Percent::from_decimal(self.observed_mean)
+    }
+
+    pub fn naive_standard_error(&self) -> Percent {
+        Percent::from_decimal(self.naive_estimator_variance().sqrt())
+    }
+
+    pub fn estimate(&self) -> Percent {
+        let adjustment = self.beta() * (self.control_mean - self.expected_control);
+        Percent::from_decimal(self.observed_mean - adjustment)
+    }
+
+    pub fn standard_error(&self) -> Percent {
+        Percent::from_decimal(self.estimator_variance().sqrt())
+    }
+
+    /// How many times smaller the variance of the adjusted estimate is than the naive one.
+    pub fn variance_reduction(&self) -> f64 {
+        // A value the control explains exactly leaves only rounding error behind
+        if self.estimator_variance() <= f64::EPSILON * self.naive_estimator_variance() {
+            return 1.0;
+        }
+        self.naive_estimator_variance() / self.estimator_variance()
+    }
+
+    fn naive_estimator_variance(&self) -> f64 {
+        let count = self.count as f64;
+        self.observed_sum_of_squares / (count - 1.0) / count
+    }
+
+    fn estimator_variance(&self) -> f64 {
+        let count = self.count as f64;
+        let explained = self.beta() * self.sum_of_products;
+        let residual_sum_of_squares = (self.observed_sum_of_squares - explained).max(0.0);
+        residual_sum_of_squares / (count - 2.0) / count
+    }
+}
+
+impl PriceHistoryStatisticValue for ControlVariateEstimate {
+    type Context = ControlVariateContext;
+
+    fn from_outcome(
+        outcome: &VariantOutcome,
+        _descriptor: &PriceHistoryDescriptor,
+        context: Option<&Self::Context>,
+    ) -> Result<Self, UntrackedOutcome> {
+        let context = context.expect("Expected a context");
+
+        Ok(Self {
+            expected_control: context.expected_control,
+            control_mean: outcome
+                .underlying_price_change()
+                .percent_change()
+                .as_decimal(),
+            observed_mean: (context.observable)(outcome.price_change()),
+            control_sum_of_squares: 0.0,
+            observed_sum_of_squares: 0.0,
+            sum_of_products: 0.0,
+            count: 1,
+        })
+    }
+
+    fn identity() -> Self {
+        Self {
+            expected_control: 0.0,
+            control_mean: 0.0,
+            observed_mean: 0.0,
+            control_sum_of_squares: 0.0,
+            observed_sum_of_squares: 0.0,
+            sum_of_products: 0.0,
+            count: 0,
+        }
+    }
+
+    fn reduce(a: Self, b: Self, _context: Option<&Self::Context>) -> Self {
+        let count = a.count + b.count;
+        if a.count == 0 || b.count == 0 {
+            return if a.count == 0 { b } else { a };
+        }
+
+        let control_delta = b.control_mean - a.control_mean;
+        let observed_delta = b.observed_mean - a.observed_mean;
+        let weight_b = b.count as f64 / count as f64;
+        let cross_weight = a.count as f64 * weight_b;
+
+        Self {
+            expected_control: a.expected_control,
+            control_mean: a.control_mean + control_delta * weight_b,
+            observed_mean: a.observed_mean + observed_delta * weight_b,
+            control_sum_of_squares: a.control_sum_of_squares
+                + b.control_sum_of_squares
+                + control_delta * control_delta * cross_weight,
+            observed_sum_of_squares: a.observed_sum_of_squares
+                + b.observed_sum_of_squares
+                + observed_delta * observed_delta * cross_weight,
+            sum_of_products: a.sum_of_products
+                + b.sum_of_products
+                + control_delta * observed_delta * cross_weight,
+            count,
+        }
+    }
+}
+
+impl GroupedStatisticValue for ControlVariateEstimate {
+    fn collapse_group(self) -> Self {
+        Self {
+            control_sum_of_squares: 0.0,
+            observed_sum_of_squares: 0.0,
+            sum_of_products: 0.0,
+            count: 1,
+            ..self
+        }
+    }
+}
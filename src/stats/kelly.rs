@@ -0,0 +1,166 @@
+use crate::{
+    number::Percent,
+    pricing::{Leverage, Period, PriceChange, PriceHistoryDescriptor},
+};
+
+use super::PriceChangeStatisticValue;
+
+/// The average log of the wealth paths end with, which is what growth-optimal (Kelly)
+/// leverage maximizes. A single path that loses everything makes it negative infinity.
+#[derive(Debug, Clone, Copy)]
+pub struct LogWealth {
+    log_sum: f64,
+    count: u64,
+}
+
+impl LogWealth {
+    pub fn expected(&self) -> f64 {
+        self.log_sum / self.count as f64
+    }
+
+    /// The growth rate per year implied by the expected log wealth.
+    pub fn annualized_growth(&self, period: Period) -> PriceChange {
+        let growth = f64::exp(self.expected() / period.as_years());
+        Percent::from_multiplier(growth).into()
+    }
+}
+
+impl PriceChangeStatisticValue for LogWealth {
+    type Context = ();
+
+    fn identity() -> Self {
+        Self {
+            log_sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn new(
+        price_change: PriceChange,
+        _descriptor: &PriceHistoryDescriptor,
+        _context: Option<&Self::Context>,
+    ) -> Self {
+        Self {
+            log_sum: log_wealth(price_change),
+            count: 1,
+        }
+    }
+
+    fn reduce(a: Self, b: Self, _context: Option<&Self::Context>) -> Self {
+        Self {
+            log_sum: a.log_sum + b.log_sum,
+            count: a.count + b.count,
+        }
+    }
+}
+
+/// The log of the wealth left after a price change, negative infinity if nothing is left.
+pub fn log_wealth(price_change: PriceChange) -> f64 {
+    let multiplier = price_change.percent_change().as_multiplier();
+    match multiplier > 0.0 {
+        true => multiplier.ln(),
+        false => f64::NEG_INFINITY,
+    }
+}
+
+/// The growth-optimal leverage from the mean and variance of daily returns.
+///
+/// With daily returns of mean `μ` and variance `σ²`, borrowing at `r` for any leverage above
+/// 1x, log growth is about `Lμ - (L - 1)r - L²σ²/2`, which peaks at `(μ - r) / σ²` once
+/// levered. Below 1x the rest earns the cash rate `c` instead, which peaks at
+/// `(μ - c) / σ²`. Fees, fat tails and volatility clustering are ignored. Without any
+/// variance to trade off against there's no optimum, so that gives `None`.
+pub fn analytic_kelly_leverage(
+    daily_price_changes: &[PriceChange],
+    daily_borrowing_rate: Percent,
+    daily_cash_rate: Percent,
+) -> Option<Leverage> {
+    let count = daily_price_changes.len() as f64;
+    let mean = daily_price_changes
+        .iter()
+        .map(|price_change| price_change.percent_change().as_decimal())
+        .sum::<f64>()
+        / count;
+    let variance = daily_price_changes
+        .iter()
+        .map(|price_change| (price_change.percent_change().as_decimal() - mean).powi(2))
+        .sum::<f64>()
+        / (count - 1.0);
+    if !variance.is_finite() || variance <= 0.0 {
+        return None;
+    }
+
+    let levered = (mean - daily_borrowing_rate.as_decimal()) / variance;
+    let unlevered = (mean - daily_cash_rate.as_decimal()) / variance;
+    let leverage = if levered >= 1.0 {
+        levered
+    } else {
+        f64::min(unlevered, 1.0)
+    };
+    Some(Leverage::new(leverage))
+}
+
+/// The leverage with the highest growth among the candidates, skipping any that can't be
+/// compared, like one that lost everything on some path and so has no finite log wealth.
+pub fn growth_optimal_leverage(
+    candidates: impl IntoIterator<Item = (Leverage, f64)>,
+) -> Option<Leverage> {
+    candidates
+        .into_iter()
+        .filter(|(_, growth)| growth.is_finite())
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(leverage, _)| leverage)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_kelly_leverage() {
+        // Alternating +3% and -1% days have a mean of 1% and deviate from it by 2%
+        let daily_price_changes = [3.0, -1.0, 3.0, -1.0]
+            .iter()
+            .map(|&change| PriceChange::from(Percent::from_percent(change)))
+            .collect::<Vec<_>>();
+        let variance = 4.0 * 0.02f64.powi(2) / 3.0;
+
+        let free = analytic_kelly_leverage(&daily_price_changes, Percent::zero(), Percent::zero());
+        assert!((free.unwrap().amount() - 0.01 / variance).abs() < 1e-5);
+
+        // Borrowing at the mean return leaves nothing to lever up for
+        let expensive = analytic_kelly_leverage(
+            &daily_price_changes,
+            Percent::from_percent(1.0),
+            Percent::zero(),
+        );
+        assert!((expensive.unwrap().amount() - 1.0).abs() < 1e-5);
+
+        // Cash paying nearly as much as the index makes it worth holding little of it
+        let cash_heavy = analytic_kelly_leverage(
+            &daily_price_changes,
+            Percent::from_percent(1.0),
+            Percent::from_percent(0.99),
+        );
+        assert!((cash_heavy.unwrap().amount() - 0.0001 / variance).abs() < 1e-5);
+
+        // Days that never vary have no optimum
+        let flat = [PriceChange::from(Percent::from_percent(1.0)); 4];
+        assert_eq!(
+            analytic_kelly_leverage(&flat, Percent::zero(), Percent::zero()),
+            None
+        );
+        assert_eq!(
+            analytic_kelly_leverage(&flat[..1], Percent::zero(), Percent::zero()),
+            None
+        );
+
+        let optimal = growth_optimal_leverage([
+            (Leverage::new(1.0), 0.1),
+            (Leverage::new(2.0), 0.3),
+            (Leverage::new(3.0), f64::NEG_INFINITY),
+        ]);
+        assert_eq!(optimal, Some(Leverage::new(2.0)));
+        assert_eq!(log_wealth(PriceChange::total_loss()), f64::NEG_INFINITY);
+    }
+}
@@ -0,0 +1,85 @@
+use crate::{
+    number::Percent,
+    pricing::{PriceHistoryDescriptor, VariantOutcome},
+};
+
+use super::{PriceHistoryStatisticValue, UntrackedOutcome};
+
+/// The distribution across paths of the average leverage each path actually held, along
+/// with the extremes held on any single day.
+#[derive(Debug, Clone)]
+pub struct RealizedLeverageDistribution {
+    averages: Vec<f64>,
+    min: f64,
+    max: f64,
+}
+
+impl RealizedLeverageDistribution {
+    pub fn mean(&self) -> f64 {
+        self.averages.iter().sum::<f64>() / self.averages.len() as f64
+    }
+
+    /// A percentile of the per-path average leverage.
+    pub fn percentile(&self, percentile: Percent) -> f64 {
+        let index = percentile.as_decimal() * self.averages.len() as f64;
+        let index = usize::min(f64::floor(index) as usize, self.averages.len() - 1);
+        self.averages[index]
+    }
+
+    /// The lowest leverage held on any day of any path.
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    /// The highest leverage held on any day of any path.
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+}
+
+impl PriceHistoryStatisticValue for RealizedLeverageDistribution {
+    type Context = ();
+
+    fn identity() -> Self {
+        Self {
+            averages: Vec::new(),
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    fn from_outcome(
+        outcome: &VariantOutcome,
+        _descriptor: &PriceHistoryDescriptor,
+        _context: Option<&Self::Context>,
+    ) -> Result<Self, UntrackedOutcome> {
+        let realized_leverage = outcome.realized_leverage();
+        Ok(Self {
+            averages: vec![realized_leverage.average()],
+            min: realized_leverage.min(),
+            max: realized_leverage.max(),
+        })
+    }
+
+    fn reduce(a: Self, b: Self, _context: Option<&Self::Context>) -> Self {
+        let mut averages = Vec::with_capacity(a.averages.len() + b.averages.len());
+        let (mut cursor_a, mut cursor_b) = (0, 0);
+        while cursor_a < a.averages.len() && cursor_b < b.averages.len() {
+            if a.averages[cursor_a] < b.averages[cursor_b] {
+                averages.push(a.averages[cursor_a]);
+                cursor_a += 1;
+            } else {
+                averages.push(b.averages[cursor_b]);
+                cursor_b += 1;
+            }
+        }
+        averages.extend_from_slice(&a.averages[cursor_a..]);
+        averages.extend_from_slice(&b.averages[cursor_b..]);
+
+        Self {
+            averages,
+            min: f64::min(a.min, b.min),
+            max: f64::max(a.max, b.max),
+        }
+    }
+}
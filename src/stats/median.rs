@@ -1,11 +1,9 @@
-use std::usize;
-
 use crate::{
     number::Percent,
     pricing::{PriceChange, PriceHistoryDescriptor},
 };
 
-use super::PriceHistoryStatisticValue;
+use super::PriceChangeStatisticValue;
 
 #[derive(Debug, Clone)]
 pub struct MedianPriceChange {
@@ -56,7 +54,7 @@ impl MedianPriceChange {
     }
 }
 
-impl PriceHistoryStatisticValue for MedianPriceChange {
+impl PriceChangeStatisticValue for MedianPriceChange {
     type Context = ();
 
     fn new(
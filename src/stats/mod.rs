@@ -1,9 +1,36 @@
 mod average;
+mod cash_flow;
+mod control_variate;
+mod kelly;
+mod leverage;
 mod median;
+mod price_return;
 mod ratio;
+mod real;
+mod retirement;
+mod ruin;
+mod standard_error;
+#[allow(clippy::module_inception)]
 mod stats;
+mod stops;
+mod target;
+mod taxes;
+mod trading_cost;
 
 pub use average::*;
+pub use cash_flow::*;
+pub use control_variate::*;
+pub use kelly::*;
+pub use leverage::*;
 pub use median::*;
+pub use price_return::*;
 pub use ratio::*;
+pub use real::*;
+pub use retirement::*;
+pub use ruin::*;
+pub use standard_error::*;
 pub use stats::*;
+pub use stops::*;
+pub use target::*;
+pub use taxes::*;
+pub use trading_cost::*;
@@ -0,0 +1,38 @@
+use crate::pricing::{PriceHistoryDescriptor, VariantOutcome};
+
+use super::{PriceChangeStatisticValue, PriceHistoryStatisticValue, UntrackedOutcome};
+
+/// Any price change statistic, computed over each path's return had the index paid no
+/// dividends instead of its total return. E.g. `PriceReturn<AveragePriceChange>` is the
+/// average price return.
+#[derive(Debug, Clone, Copy)]
+pub struct PriceReturn<T>(T);
+
+impl<T> PriceReturn<T> {
+    pub fn value(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> PriceHistoryStatisticValue for PriceReturn<T>
+where
+    T: PriceChangeStatisticValue,
+{
+    type Context = T::Context;
+
+    fn identity() -> Self {
+        Self(T::identity())
+    }
+
+    fn from_outcome(
+        outcome: &VariantOutcome,
+        descriptor: &PriceHistoryDescriptor,
+        context: Option<&Self::Context>,
+    ) -> Result<Self, UntrackedOutcome> {
+        Ok(Self(T::new(outcome.price_return(), descriptor, context)))
+    }
+
+    fn reduce(a: Self, b: Self, context: Option<&Self::Context>) -> Self {
+        Self(T::reduce(a.0, b.0, context))
+    }
+}
@@ -8,7 +8,7 @@ use crate::{
     types::Predicate,
 };
 
-use super::PriceHistoryStatisticValue;
+use super::PriceChangeStatisticValue;
 
 #[derive(Debug, Clone, Copy)]
 pub struct MatchingPriceChangeRatio {
@@ -16,6 +16,7 @@ pub struct MatchingPriceChangeRatio {
     count: u64,
 }
 
+#[allow(dead_code)]
 impl MatchingPriceChangeRatio {
     pub fn success_percent(&self) -> Percent {
         let decimal = self.matching_count as f64 / self.count as f64;
@@ -27,6 +28,22 @@ impl MatchingPriceChangeRatio {
         let decimal = 1.0 - decimal;
         Percent::from_decimal(decimal)
     }
+
+    /// The Wilson score interval around the success share, `z` standard errors wide. Paths
+    /// are treated as independent, so under antithetic sampling, whose paired paths offset
+    /// each other's errors, the band is conservative (a little wide).
+    pub fn confidence_interval(&self, z: f64) -> (Percent, Percent) {
+        let count = self.count as f64;
+        let share = self.matching_count as f64 / count;
+        let z_squared = z * z;
+        let center = (share + z_squared / (2.0 * count)) / (1.0 + z_squared / count);
+        let half_width = z / (1.0 + z_squared / count)
+            * f64::sqrt(share * (1.0 - share) / count + z_squared / (4.0 * count * count));
+        (
+            Percent::from_decimal(center - half_width),
+            Percent::from_decimal(center + half_width),
+        )
+    }
 }
 
 pub struct MatchingPriceChangeRatioContext {
@@ -44,7 +61,7 @@ impl MatchingPriceChangeRatioContext {
     }
 }
 
-impl PriceHistoryStatisticValue for MatchingPriceChangeRatio {
+impl PriceChangeStatisticValue for MatchingPriceChangeRatio {
     type Context = MatchingPriceChangeRatioContext;
 
     fn identity() -> Self {
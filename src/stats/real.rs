@@ -0,0 +1,41 @@
+use crate::pricing::{PriceHistoryDescriptor, VariantOutcome};
+
+use super::{PriceChangeStatisticValue, PriceHistoryStatisticValue, UntrackedOutcome};
+
+/// Any price change statistic, computed over each path's return after inflation instead of
+/// its nominal one. E.g. `Real<AveragePriceChange>` is the average real return.
+#[derive(Debug, Clone, Copy)]
+pub struct Real<T>(T);
+
+impl<T> Real<T> {
+    pub fn value(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> PriceHistoryStatisticValue for Real<T>
+where
+    T: PriceChangeStatisticValue,
+{
+    type Context = T::Context;
+
+    fn identity() -> Self {
+        Self(T::identity())
+    }
+
+    fn from_outcome(
+        outcome: &VariantOutcome,
+        descriptor: &PriceHistoryDescriptor,
+        context: Option<&Self::Context>,
+    ) -> Result<Self, UntrackedOutcome> {
+        Ok(Self(T::new(
+            outcome.real_price_change(),
+            descriptor,
+            context,
+        )))
+    }
+
+    fn reduce(a: Self, b: Self, context: Option<&Self::Context>) -> Self {
+        Self(T::reduce(a.0, b.0, context))
+    }
+}
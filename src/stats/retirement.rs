@@ -0,0 +1,74 @@
+use crate::{
+    number::Percent,
+    pricing::{PriceHistoryDescriptor, VariantOutcome},
+};
+
+use super::{
+    cash_flow::{merge_sorted, percentile_of},
+    PriceHistoryStatisticValue, UntrackedOutcome,
+};
+
+/// How retirements went across paths: how often they ran out, what was left at the end,
+/// and how much could have been withdrawn.
+#[derive(Debug, Clone)]
+pub struct RetirementDistribution {
+    terminal_wealths: Vec<f64>,
+    safe_withdrawal_rates: Vec<f64>,
+    depleted_count: u64,
+    count: u64,
+}
+
+impl RetirementDistribution {
+    /// Share of paths where every withdrawal was made in full.
+    pub fn success_probability(&self) -> Percent {
+        Percent::from_decimal(1.0 - self.depleted_count as f64 / self.count as f64)
+    }
+
+    pub fn terminal_wealth_percentile(&self, percentile: Percent) -> f64 {
+        percentile_of(&self.terminal_wealths, percentile)
+    }
+
+    /// The highest fixed withdrawal rate that would have lasted on `confidence` of paths.
+    pub fn safe_withdrawal_rate(&self, confidence: Percent) -> Percent {
+        let percentile = Percent::from_decimal(1.0 - confidence.as_decimal());
+        Percent::from_decimal(percentile_of(&self.safe_withdrawal_rates, percentile))
+    }
+}
+
+impl PriceHistoryStatisticValue for RetirementDistribution {
+    type Context = ();
+
+    fn identity() -> Self {
+        Self {
+            terminal_wealths: Vec::new(),
+            safe_withdrawal_rates: Vec::new(),
+            depleted_count: 0,
+            count: 0,
+        }
+    }
+
+    fn from_outcome(
+        outcome: &VariantOutcome,
+        _descriptor: &PriceHistoryDescriptor,
+        _context: Option<&Self::Context>,
+    ) -> Result<Self, UntrackedOutcome> {
+        let retirement = outcome
+            .retirement()
+            .ok_or(UntrackedOutcome::new("Retirement"))?;
+        Ok(Self {
+            terminal_wealths: vec![retirement.terminal_wealth()],
+            safe_withdrawal_rates: vec![retirement.safe_withdrawal_rate().as_decimal()],
+            depleted_count: retirement.depleted() as u64,
+            count: 1,
+        })
+    }
+
+    fn reduce(a: Self, b: Self, _context: Option<&Self::Context>) -> Self {
+        Self {
+            terminal_wealths: merge_sorted(&a.terminal_wealths, &b.terminal_wealths),
+            safe_withdrawal_rates: merge_sorted(&a.safe_withdrawal_rates, &b.safe_withdrawal_rates),
+            depleted_count: a.depleted_count + b.depleted_count,
+            count: a.count + b.count,
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use crate::{
+    number::Percent,
+    pricing::{PriceHistoryDescriptor, RuinCause, VariantOutcome},
+};
+
+use super::{PriceHistoryStatisticValue, UntrackedOutcome};
+
+/// How often paths were wiped out or terminated, and how early it tended to happen.
+#[derive(Debug, Clone, Copy)]
+pub struct RuinProbability {
+    total_loss_count: u64,
+    terminated_count: u64,
+    ruin_day_sum: u64,
+    count: u64,
+}
+
+impl RuinProbability {
+    /// Share of paths that were ruined for any reason.
+    pub fn probability(&self) -> Percent {
+        self.share(self.ruined_count())
+    }
+
+    pub fn total_loss_probability(&self) -> Percent {
+        self.share(self.total_loss_count)
+    }
+
+    pub fn termination_probability(&self) -> Percent {
+        self.share(self.terminated_count)
+    }
+
+    /// The average day on which ruined paths were ruined.
+    pub fn average_ruin_day(&self) -> Option<f64> {
+        match self.ruined_count() {
+            0 => None,
+            ruined => Some(self.ruin_day_sum as f64 / ruined as f64),
+        }
+    }
+
+    fn ruined_count(&self) -> u64 {
+        self.total_loss_count + self.terminated_count
+    }
+
+    fn share(&self, matching: u64) -> Percent {
+        Percent::from_decimal(matching as f64 / self.count as f64)
+    }
+}
+
+impl PriceHistoryStatisticValue for RuinProbability {
+    type Context = ();
+
+    fn identity() -> Self {
+        Self {
+            total_loss_count: 0,
+            terminated_count: 0,
+            ruin_day_sum: 0,
+            count: 0,
+        }
+    }
+
+    fn from_outcome(
+        outcome: &VariantOutcome,
+        _descriptor: &PriceHistoryDescriptor,
+        _context: Option<&Self::Context>,
+    ) -> Result<Self, UntrackedOutcome> {
+        let mut statistic = Self {
+            count: 1,
+            ..Self::identity()
+        };
+
+        if let Some(ruin) = outcome.ruin() {
+            statistic.ruin_day_sum = ruin.day() as u64;
+            match ruin.cause() {
+                RuinCause::TotalLoss => statistic.total_loss_count = 1,
+                RuinCause::Terminated => statistic.terminated_count = 1,
+            }
+        }
+
+        Ok(statistic)
+    }
+
+    fn reduce(a: Self, b: Self, _context: Option<&Self::Context>) -> Self {
+        Self {
+            total_loss_count: a.total_loss_count + b.total_loss_count,
+            terminated_count: a.terminated_count + b.terminated_count,
+            ruin_day_sum: a.ruin_day_sum + b.ruin_day_sum,
+            count: a.count + b.count,
+        }
+    }
+}
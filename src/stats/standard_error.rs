@@ -0,0 +1,129 @@
+use crate::{
+    number::Percent,
+    pricing::{PriceChange, PriceHistoryDescriptor},
+};
+
+use super::{GroupedStatisticValue, PriceChangeStatisticValue};
+
+/// Sample mean and variance of the total price change, tracked with a mergeable form of
+/// Welford's algorithm so the parallel reduction stays numerically stable.
+#[derive(Debug, Clone, Copy)]
+pub struct StandardErrorPriceChange {
+    mean: f64,
+    squared_deviation_sum: f64,
+    count: u64,
+}
+
+impl StandardErrorPriceChange {
+    pub fn mean(&self) -> PriceChange {
+        Percent::from_decimal(self.mean).into()
+    }
+
+    /// Unbiased sample variance of the observations.
+    pub fn variance(&self) -> f64 {
+        self.squared_deviation_sum / (self.count - 1) as f64
+    }
+
+    /// Variance of the mean as an estimator.
+    pub fn estimator_variance(&self) -> f64 {
+        self.variance() / self.count as f64
+    }
+
+    pub fn standard_error(&self) -> PriceChange {
+        Percent::from_decimal(self.estimator_variance().sqrt()).into()
+    }
+
+    /// How many times smaller this estimator's variance is than the baseline's.
+    pub fn variance_reduction(&self, baseline: &Self) -> f64 {
+        baseline.estimator_variance() / self.estimator_variance()
+    }
+}
+
+impl PriceChangeStatisticValue for StandardErrorPriceChange {
+    type Context = ();
+
+    fn new(
+        price_change: PriceChange,
+        _descriptor: &PriceHistoryDescriptor,
+        _context: Option<&Self::Context>,
+    ) -> Self {
+        Self {
+            mean: price_change.percent_change().as_decimal(),
+            squared_deviation_sum: 0.0,
+            count: 1,
+        }
+    }
+
+    fn identity() -> Self {
+        Self {
+            mean: 0.0,
+            squared_deviation_sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn reduce(a: Self, b: Self, _context: Option<&Self::Context>) -> Self {
+        let count = a.count + b.count;
+        if count == 0 {
+            return Self::identity();
+        }
+
+        let delta = b.mean - a.mean;
+        let weight_b = b.count as f64 / count as f64;
+        Self {
+            mean: a.mean + delta * weight_b,
+            squared_deviation_sum: a.squared_deviation_sum
+                + b.squared_deviation_sum
+                + delta * delta * a.count as f64 * weight_b,
+            count,
+        }
+    }
+}
+
+impl GroupedStatisticValue for StandardErrorPriceChange {
+    fn collapse_group(self) -> Self {
+        Self {
+            mean: self.mean,
+            squared_deviation_sum: 0.0,
+            count: 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pricing::{ExpenseRatio, Leverage, LeverageMode, Period, PriceHistory};
+
+    use super::*;
+
+    #[test]
+    fn test_reduce_matches_direct_variance() {
+        let decimals = [0.1, -0.2, 0.35, 0.05, -0.4];
+        let price_history: PriceHistory = decimals
+            .iter()
+            .map(|&decimal| Percent::from_decimal(decimal).into())
+            .collect();
+        let descriptor = PriceHistoryDescriptor::new(
+            Leverage::new(1.0),
+            LeverageMode::DailyReset,
+            ExpenseRatio::zero(),
+            Period::Years(1),
+        );
+
+        let reduced = price_history
+            .iter()
+            .map(|&price_change| StandardErrorPriceChange::new(price_change, &descriptor, None))
+            .fold(StandardErrorPriceChange::identity(), |a, b| {
+                StandardErrorPriceChange::reduce(a, b, None)
+            });
+
+        let mean = decimals.iter().sum::<f64>() / 5.0;
+        let variance = decimals
+            .iter()
+            .map(|d| (d - mean) * (d - mean))
+            .sum::<f64>()
+            / 4.0;
+        assert!((reduced.mean().percent_change().as_decimal() - mean).abs() < 1e-12);
+        assert!((reduced.variance() - variance).abs() < 1e-12);
+    }
+}
@@ -1,10 +1,15 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, error::Error, fmt::Display};
 
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use rayon::{
+    iter::{IntoParallelIterator, ParallelIterator},
+    slice::ParallelSlice,
+};
 
 use crate::{
     number::Percent,
-    pricing::{PriceChange, PriceHistoryDescriptor, PriceHistoryVariants},
+    pricing::{
+        PathSampling, PriceChange, PriceHistoryDescriptor, PriceHistoryVariants, VariantOutcome,
+    },
 };
 
 pub struct PriceHistoryStatistic<T>
@@ -38,34 +43,90 @@ where
     }
 }
 
+/// Something a statistic needed from the paths that wasn't tracked for them, e.g. cash flows
+/// when none were simulated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UntrackedOutcome {
+    outcome: &'static str,
+}
+
+impl UntrackedOutcome {
+    pub fn new(outcome: &'static str) -> Self {
+        Self { outcome }
+    }
+}
+
+impl Display for UntrackedOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} weren't tracked for these paths", self.outcome)
+    }
+}
+
+impl Error for UntrackedOutcome {}
+
 pub fn calculate_statistic<T, CTX>(
     variants: &[PriceHistoryVariants],
     context: Option<&HashMap<PriceHistoryDescriptor, CTX>>,
-) -> impl Iterator<Item = ComputedStatistic<T>>
+) -> Result<impl Iterator<Item = ComputedStatistic<T>>, UntrackedOutcome>
 where
     CTX: Send + Sync,
     T: PriceHistoryStatisticValue<Context = CTX> + Clone,
 {
-    let mut variant_statistics = variants
+    let variant_statistics = variants
         .into_par_iter()
         .cloned()
         .map(|variant| PriceHistoryStatistic::<T>::new(variant, context))
-        .reduce_with(|a, b| {
-            merge_statistics(a, b, context, |left, right, ctx| {
+        .try_reduce_with(|a, b| {
+            Ok(merge_statistics(a, b, context, |left, right, ctx| {
                 T::reduce(left, right, ctx)
-            })
+            }))
         })
-        .unwrap();
+        .unwrap()?;
 
-    (0..variant_statistics.depth).into_iter().map(move |_| {
-        let descriptor = variant_statistics.descriptors.remove(0);
-        let statistic = variant_statistics.values.remove(0);
+    Ok(variant_statistics.into_computed())
+}
 
-        ComputedStatistic {
-            descriptor,
-            statistic,
-        }
-    })
+/// Like [`calculate_statistic`], but each group of paths that were drawn together is first
+/// collapsed into a single observation, so that estimates of error treat the group as the
+/// independent unit.
+pub fn calculate_grouped_statistic<T, CTX>(
+    variants: &[PriceHistoryVariants],
+    path_sampling: PathSampling,
+    context: Option<&HashMap<PriceHistoryDescriptor, CTX>>,
+) -> Result<impl Iterator<Item = ComputedStatistic<T>>, UntrackedOutcome>
+where
+    CTX: Send + Sync,
+    T: GroupedStatisticValue<Context = CTX> + Clone,
+{
+    let reduce = |a, b| {
+        merge_statistics(a, b, context, |left, right, ctx| {
+            T::reduce(left, right, ctx)
+        })
+    };
+
+    let group_size = path_sampling.paths_per_draw() as usize;
+    let variant_statistics = variants
+        .par_chunks(group_size)
+        .map(|group| {
+            let mut group_statistic = group
+                .iter()
+                .cloned()
+                .map(|variant| PriceHistoryStatistic::<T>::new(variant, context))
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .reduce(reduce)
+                .unwrap();
+            group_statistic.values = group_statistic
+                .values
+                .into_iter()
+                .map(T::collapse_group)
+                .collect();
+            Ok(group_statistic)
+        })
+        .try_reduce_with(|a, b| Ok(reduce(a, b)))
+        .unwrap()?;
+
+    Ok(variant_statistics.into_computed())
 }
 
 impl<T> PriceHistoryStatistic<T>
@@ -75,34 +136,66 @@ where
     fn new(
         variants: PriceHistoryVariants,
         context: Option<&HashMap<PriceHistoryDescriptor, T::Context>>,
-    ) -> Self {
+    ) -> Result<Self, UntrackedOutcome> {
         let descriptors = Vec::from(variants.descriptors());
-        let values: Vec<T> = variants
-            .total_price_changes()
+        let values: Vec<T> = descriptors
             .iter()
-            .copied()
             .enumerate()
-            .map(|(i, price_change)| {
-                let descriptor = descriptors[i];
-                let ctx = context.map(|map| &map[&descriptor]);
-                T::new(price_change, &descriptor, ctx)
+            .map(|(i, descriptor)| {
+                let ctx = context.map(|map| &map[descriptor]);
+                T::from_outcome(&variants.outcome(i), descriptor, ctx)
             })
-            .collect();
+            .collect::<Result<_, _>>()?;
 
         debug_assert_eq!(descriptors.len(), values.len());
         let depth = values.len();
 
-        PriceHistoryStatistic {
+        Ok(PriceHistoryStatistic {
             depth,
             descriptors,
             values,
-        }
+        })
+    }
+
+    fn into_computed(mut self) -> impl Iterator<Item = ComputedStatistic<T>>
+    where
+        T: Clone,
+    {
+        (0..self.depth).map(move |_| {
+            let descriptor = self.descriptors.remove(0);
+            let statistic = self.values.remove(0);
+
+            ComputedStatistic {
+                descriptor,
+                statistic,
+            }
+        })
     }
 }
 
 pub trait PriceHistoryStatisticValue: Send + Sync {
     type Context;
 
+    fn identity() -> Self;
+
+    /// Builds the value for a single path from everything recorded about it, such as the
+    /// underlying (1x) price change or how it was ruined. Fails if the statistic needs
+    /// something that wasn't tracked for the path.
+    fn from_outcome(
+        outcome: &VariantOutcome,
+        descriptor: &PriceHistoryDescriptor,
+        context: Option<&Self::Context>,
+    ) -> Result<Self, UntrackedOutcome>
+    where
+        Self: Sized;
+
+    fn reduce(a: Self, b: Self, context: Option<&Self::Context>) -> Self;
+}
+
+/// A statistic that only needs each path's total price change.
+pub trait PriceChangeStatisticValue: Send + Sync {
+    type Context;
+
     fn identity() -> Self;
     fn new(
         price_change: PriceChange,
@@ -112,56 +205,26 @@ pub trait PriceHistoryStatisticValue: Send + Sync {
     fn reduce(a: Self, b: Self, context: Option<&Self::Context>) -> Self;
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct AveragePriceChange {
-    price_change_sum: f64,
-    annualized_price_change_sum: f64,
-    count: u64,
-}
-
-impl AveragePriceChange {
-    pub fn average(&self) -> PriceChange {
-        Percent::from_decimal(self.price_change_sum / (self.count as f64)).into()
-    }
+impl<T> PriceHistoryStatisticValue for T
+where
+    T: PriceChangeStatisticValue,
+{
+    type Context = T::Context;
 
-    pub fn annualized_average(&self) -> PriceChange {
-        Percent::from_decimal(self.annualized_price_change_sum / (self.count as f64)).into()
+    fn identity() -> Self {
+        <T as PriceChangeStatisticValue>::identity()
     }
-}
-
-impl PriceHistoryStatisticValue for AveragePriceChange {
-    type Context = ();
 
-    fn new(
-        price_change: PriceChange,
+    fn from_outcome(
+        outcome: &VariantOutcome,
         descriptor: &PriceHistoryDescriptor,
-        _context: Option<&Self::Context>,
-    ) -> Self {
-        Self {
-            price_change_sum: price_change.percent_change().as_decimal(),
-            annualized_price_change_sum: price_change
-                .annualized_return(descriptor.period())
-                .percent_change()
-                .as_decimal(),
-            count: 1,
-        }
-    }
-
-    fn identity() -> Self {
-        Self {
-            price_change_sum: 0.0,
-            annualized_price_change_sum: 0.0,
-            count: 0,
-        }
+        context: Option<&Self::Context>,
+    ) -> Result<Self, UntrackedOutcome> {
+        Ok(T::new(outcome.price_change(), descriptor, context))
     }
 
-    fn reduce(a: Self, b: Self, _context: Option<&Self::Context>) -> Self {
-        Self {
-            price_change_sum: a.price_change_sum + b.price_change_sum,
-            annualized_price_change_sum: a.annualized_price_change_sum
-                + b.annualized_price_change_sum,
-            count: a.count + b.count,
-        }
+    fn reduce(a: Self, b: Self, context: Option<&Self::Context>) -> Self {
+        <T as PriceChangeStatisticValue>::reduce(a, b, context)
     }
 }
 
@@ -191,12 +254,12 @@ impl StandardDeviationPriceChange {
     }
 }
 
-impl PriceHistoryStatisticValue for StandardDeviationPriceChange {
+impl PriceChangeStatisticValue for StandardDeviationPriceChange {
     type Context = StandardDeviationPriceChangeContext;
 
     fn new(
         price_change: PriceChange,
-        descriptor: &PriceHistoryDescriptor,
+        _descriptor: &PriceHistoryDescriptor,
         context: Option<&Self::Context>,
     ) -> Self {
         let context = context.expect("Expected a context");
@@ -224,6 +287,13 @@ impl PriceHistoryStatisticValue for StandardDeviationPriceChange {
     }
 }
 
+/// A statistic built from moments of the paths, which can treat a group of correlated paths
+/// as one observation at the group's mean.
+pub trait GroupedStatisticValue: PriceHistoryStatisticValue {
+    /// Collapses the statistic of a single group into one observation.
+    fn collapse_group(self) -> Self;
+}
+
 fn merge_statistics<T, CTX, F>(
     mut stat_a: PriceHistoryStatistic<T>,
     mut stat_b: PriceHistoryStatistic<T>,
@@ -241,9 +311,8 @@ where
     let descriptors = stat_a.descriptors;
 
     let mut values = Vec::with_capacity(depth);
-    for i in 0..depth {
-        let descriptor = descriptors[i];
-        let ctx = context.map(|map| &map[&descriptor]);
+    for descriptor in descriptors.iter() {
+        let ctx = context.map(|map| &map[descriptor]);
 
         let value_a = stat_a.values.remove(0);
         let value_b = stat_b.values.remove(0);
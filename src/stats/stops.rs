@@ -0,0 +1,170 @@
+use crate::{
+    number::Percent,
+    pricing::{PriceHistoryDescriptor, VariantOutcome},
+};
+
+use super::{
+    cash_flow::{merge_sorted, percentile_of},
+    PriceHistoryStatisticValue, UntrackedOutcome,
+};
+
+/// How often a stop triggered across paths, what its whipsaws cost, and how the stopped
+/// variant compares with holding throughout.
+#[derive(Debug, Clone)]
+pub struct StopDistribution {
+    whipsaw_costs: Vec<f64>,
+    // Ending equity of each path relative to never being stopped out
+    relative_outcomes: Vec<f64>,
+    triggers: u64,
+    triggered_count: u64,
+    time_out_sum: f64,
+    count: u64,
+}
+
+impl StopDistribution {
+    pub fn mean_triggers(&self) -> f64 {
+        self.triggers as f64 / self.count as f64
+    }
+
+    /// Share of paths where the stop triggered at least once.
+    pub fn trigger_probability(&self) -> Percent {
+        Percent::from_decimal(self.triggered_count as f64 / self.count as f64)
+    }
+
+    pub fn mean_time_out(&self) -> Percent {
+        Percent::from_decimal(self.time_out_sum / self.count as f64)
+    }
+
+    pub fn mean_whipsaw_cost(&self) -> Percent {
+        Percent::from_decimal(self.whipsaw_costs.iter().sum::<f64>() / self.count as f64)
+    }
+
+    pub fn whipsaw_cost_percentile(&self, percentile: Percent) -> Percent {
+        Percent::from_decimal(percentile_of(&self.whipsaw_costs, percentile))
+    }
+
+    /// A percentile of how much more, or less, a path ended with than had it never been
+    /// stopped out. Paths that would have been lost in full are left out.
+    pub fn relative_outcome_percentile(&self, percentile: Percent) -> Option<Percent> {
+        match self.relative_outcomes.is_empty() {
+            true => None,
+            false => Some(Percent::from_decimal(percentile_of(
+                &self.relative_outcomes,
+                percentile,
+            ))),
+        }
+    }
+}
+
+impl PriceHistoryStatisticValue for StopDistribution {
+    type Context = ();
+
+    fn identity() -> Self {
+        Self {
+            whipsaw_costs: Vec::new(),
+            relative_outcomes: Vec::new(),
+            triggers: 0,
+            triggered_count: 0,
+            time_out_sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn from_outcome(
+        outcome: &VariantOutcome,
+        descriptor: &PriceHistoryDescriptor,
+        _context: Option<&Self::Context>,
+    ) -> Result<Self, UntrackedOutcome> {
+        // Only variants with a stop have anything to show for it
+        if descriptor.stop().is_none() {
+            return Ok(Self::identity());
+        }
+        let stops = outcome.stops().ok_or(UntrackedOutcome::new("Stops"))?;
+        let stopped = outcome.price_change().percent_change().as_multiplier();
+        let unstopped = stops
+            .unstopped_price_change()
+            .percent_change()
+            .as_multiplier();
+        Ok(Self {
+            whipsaw_costs: vec![stops.whipsaw_cost().as_decimal()],
+            relative_outcomes: match unstopped > 0.0 {
+                true => vec![stopped / unstopped - 1.0],
+                false => Vec::new(),
+            },
+            triggers: stops.triggers() as u64,
+            triggered_count: (stops.triggers() > 0) as u64,
+            time_out_sum: stops.time_out().as_decimal(),
+            count: 1,
+        })
+    }
+
+    fn reduce(a: Self, b: Self, _context: Option<&Self::Context>) -> Self {
+        Self {
+            whipsaw_costs: merge_sorted(&a.whipsaw_costs, &b.whipsaw_costs),
+            relative_outcomes: merge_sorted(&a.relative_outcomes, &b.relative_outcomes),
+            triggers: a.triggers + b.triggers,
+            triggered_count: a.triggered_count + b.triggered_count,
+            time_out_sum: a.time_out_sum + b.time_out_sum,
+            count: a.count + b.count,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        number::Percent,
+        pricing::{
+            ExpenseRatioSchedule, Leverage, LeverageGrid, LeverageMode, MarketHistory,
+            OutcomeTracking, Period, PriceChange, PriceHistory, PriceHistoryVariants, Reentry,
+            StopRule, StopTrigger, TradingCosts,
+        },
+        stats::calculate_statistic,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_stop_variants() {
+        // Falls 20% on the second day
+        let period = Period::Days(3);
+        let market_history = MarketHistory::from(
+            [0.0, -20.0, 0.0]
+                .iter()
+                .map(|&change| PriceChange::from(Percent::from_percent(change)))
+                .collect::<PriceHistory>(),
+        );
+        let stop = |percent| {
+            StopRule::new(
+                StopTrigger::Loss(Percent::from_percent(percent)),
+                Reentry::After(Period::Days(10)),
+                Period::Days(0),
+            )
+        };
+        let variants = [PriceHistoryVariants::new(
+            &market_history,
+            period,
+            &LeverageGrid::new(&[Leverage::new(1.0)]),
+            &ExpenseRatioSchedule::default(),
+            &[LeverageMode::DailyReset],
+            &TradingCosts::None,
+            &OutcomeTracking::new(Vec::new(), None, None, None, vec![stop(10.0), stop(30.0)]),
+        )];
+
+        // Each stop is its own variant, next to the one without a stop
+        let stops = calculate_statistic::<StopDistribution, _>(&variants, None)
+            .unwrap()
+            .filter_map(|value| {
+                let stop = value.descriptor().stop()?;
+                Some((stop, value.statistic().trigger_probability()))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            stops,
+            vec![
+                (stop(10.0), Percent::from_percent(100.0)),
+                (stop(30.0), Percent::zero())
+            ]
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{number::Percent, pricing::Leverage};
+
+use super::MatchingPriceChangeRatio;
+
+/// Which end of the leverages that meet a target to look for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeverageBound {
+    /// The most leverage that still meets the target, e.g. for a floor on outcomes.
+    Highest,
+    /// The least leverage that meets the target, e.g. for reaching a goal.
+    Lowest,
+}
+
+/// A required probability of some outcome, solved for the leverage at which it's met.
+#[derive(Debug, Clone, Copy)]
+pub struct OutcomeTarget {
+    bound: LeverageBound,
+    probability: Percent,
+    z: f64,
+}
+
+/// The leverage meeting a target along with the band it could lie in given sampling error.
+/// `None` means no leverage on the grid meets the target.
+#[derive(Debug, Clone, Copy)]
+pub struct TargetLeverage {
+    estimate: Option<f64>,
+    low: Option<f64>,
+    high: Option<f64>,
+}
+
+impl TargetLeverage {
+    pub fn estimate(&self) -> Option<f64> {
+        self.estimate
+    }
+
+    pub fn low(&self) -> Option<f64> {
+        self.low
+    }
+
+    pub fn high(&self) -> Option<f64> {
+        self.high
+    }
+}
+
+impl OutcomeTarget {
+    /// `z` is how many standard errors wide the confidence band on each probability is.
+    pub fn new(bound: LeverageBound, probability: Percent, z: f64) -> Self {
+        Self {
+            bound,
+            probability,
+            z,
+        }
+    }
+
+    /// Finds where the probability of the outcome crosses the target across the grid,
+    /// interpolating linearly between the grid points on either side of the crossing. Only
+    /// grid leverages are simulated, so between them the answer is as good as the straight
+    /// line, and a coarse grid can miss a crossing that turns back between two points. The
+    /// band comes from solving the same way against either end of each probability's
+    /// confidence interval.
+    pub fn solve(&self, ratios: &[(Leverage, MatchingPriceChangeRatio)]) -> TargetLeverage {
+        let mut ratios = Vec::from(ratios);
+        ratios.sort_by_key(|&(leverage, _)| leverage);
+        let leverages = ratios
+            .iter()
+            .map(|(leverage, _)| leverage.amount())
+            .collect::<Vec<_>>();
+        let curve = |probability: &dyn Fn(&MatchingPriceChangeRatio) -> Percent| {
+            let probabilities = ratios
+                .iter()
+                .map(|(_, ratio)| probability(ratio).as_decimal())
+                .collect::<Vec<_>>();
+            self.crossing(&leverages, &probabilities)
+        };
+
+        let estimate = curve(&|ratio| ratio.success_percent());
+        let pessimistic = curve(&|ratio| ratio.confidence_interval(self.z).0);
+        let optimistic = curve(&|ratio| ratio.confidence_interval(self.z).1);
+        let (low, high) = match self.bound {
+            LeverageBound::Highest => (pessimistic, optimistic),
+            LeverageBound::Lowest => (optimistic, pessimistic),
+        };
+
+        TargetLeverage {
+            estimate,
+            low,
+            high,
+        }
+    }
+
+    fn crossing(&self, leverages: &[f64], probabilities: &[f64]) -> Option<f64> {
+        let target = self.probability.as_decimal();
+        let meets = |index: usize| probabilities[index] >= target;
+        let interpolate = |from: usize, to: usize| {
+            let fraction =
+                (probabilities[from] - target) / (probabilities[from] - probabilities[to]);
+            leverages[from] + fraction * (leverages[to] - leverages[from])
+        };
+
+        match self.bound {
+            LeverageBound::Highest => {
+                let index = (0..leverages.len()).rev().find(|&index| meets(index))?;
+                match index + 1 < leverages.len() {
+                    true => Some(interpolate(index, index + 1)),
+                    false => Some(leverages[index]),
+                }
+            }
+            LeverageBound::Lowest => {
+                let index = (0..leverages.len()).find(|&index| meets(index))?;
+                match index > 0 {
+                    true => Some(interpolate(index, index - 1)),
+                    false => Some(leverages[index]),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        pricing::{ExpenseRatio, LeverageMode, Period, PriceChange, PriceHistoryDescriptor},
+        stats::{MatchingPriceChangeRatioContext, PriceChangeStatisticValue},
+    };
+
+    use super::*;
+
+    fn ratio(matching: u64, count: u64) -> MatchingPriceChangeRatio {
+        let context = MatchingPriceChangeRatioContext::new(|price_change: PriceChange| {
+            price_change.percent_change() > Percent::zero()
+        });
+        let descriptor = PriceHistoryDescriptor::new(
+            Leverage::new(1.0),
+            LeverageMode::DailyReset,
+            ExpenseRatio::zero(),
+            Period::Years(1),
+        );
+        (0..count)
+            .map(|path| match path < matching {
+                true => PriceChange::from(Percent::from_percent(1.0)),
+                false => PriceChange::zero(),
+            })
+            .map(|price_change| {
+                MatchingPriceChangeRatio::new(price_change, &descriptor, Some(&context))
+            })
+            .fold(MatchingPriceChangeRatio::identity(), |a, b| {
+                MatchingPriceChangeRatio::reduce(a, b, Some(&context))
+            })
+    }
+
+    #[test]
+    fn test_target_leverage() {
+        let ratios = [
+            (Leverage::new(1.0), ratio(950, 1000)),
+            (Leverage::new(2.0), ratio(850, 1000)),
+            (Leverage::new(3.0), ratio(500, 1000)),
+        ];
+
+        // 90% falls halfway between 1x and 2x
+        let highest = OutcomeTarget::new(LeverageBound::Highest, Percent::from_percent(90.0), 2.0)
+            .solve(&ratios);
+        assert!((highest.estimate().unwrap() - 1.5).abs() < 1e-9);
+        assert!(highest.low().unwrap() < 1.5 && highest.high().unwrap() > 1.5);
+
+        let lowest = OutcomeTarget::new(LeverageBound::Lowest, Percent::from_percent(90.0), 2.0)
+            .solve(&ratios);
+        assert_eq!(lowest.estimate(), Some(1.0));
+
+        let unreachable =
+            OutcomeTarget::new(LeverageBound::Highest, Percent::from_percent(99.0), 2.0)
+                .solve(&ratios);
+        assert_eq!(unreachable.estimate(), None);
+    }
+}
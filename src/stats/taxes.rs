@@ -0,0 +1,83 @@
+use crate::{
+    number::Percent,
+    pricing::{PriceHistoryDescriptor, VariantOutcome},
+};
+
+use super::{
+    cash_flow::{merge_sorted, percentile_of},
+    PriceHistoryStatisticValue, UntrackedOutcome,
+};
+
+/// After-tax wealth across paths, next to what the same flows came to in a tax-deferred
+/// account.
+#[derive(Debug, Clone)]
+pub struct TaxDistribution {
+    after_tax_wealths: Vec<f64>,
+    tax_deferred_wealths: Vec<f64>,
+    taxes_paid_sum: f64,
+    count: u64,
+}
+
+impl TaxDistribution {
+    pub fn after_tax_wealth_percentile(&self, percentile: Percent) -> f64 {
+        percentile_of(&self.after_tax_wealths, percentile)
+    }
+
+    pub fn tax_deferred_wealth_percentile(&self, percentile: Percent) -> f64 {
+        percentile_of(&self.tax_deferred_wealths, percentile)
+    }
+
+    pub fn mean_taxes_paid(&self) -> f64 {
+        self.taxes_paid_sum / self.count as f64
+    }
+
+    /// How much less the median path ends up with in the taxable account than in the
+    /// tax-deferred one.
+    pub fn median_tax_drag(&self) -> Percent {
+        let median = Percent::from_percent(50.0);
+        Percent::from_decimal(
+            1.0 - self.after_tax_wealth_percentile(median)
+                / self.tax_deferred_wealth_percentile(median),
+        )
+    }
+}
+
+impl PriceHistoryStatisticValue for TaxDistribution {
+    type Context = ();
+
+    fn identity() -> Self {
+        Self {
+            after_tax_wealths: Vec::new(),
+            tax_deferred_wealths: Vec::new(),
+            taxes_paid_sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn from_outcome(
+        outcome: &VariantOutcome,
+        descriptor: &PriceHistoryDescriptor,
+        _context: Option<&Self::Context>,
+    ) -> Result<Self, UntrackedOutcome> {
+        // Only variants held in the account are taxed
+        if descriptor.taxes().is_none() {
+            return Ok(Self::identity());
+        }
+        let taxes = outcome.taxes().ok_or(UntrackedOutcome::new("Taxes"))?;
+        Ok(Self {
+            after_tax_wealths: vec![taxes.after_tax_wealth()],
+            tax_deferred_wealths: vec![taxes.tax_deferred_wealth()],
+            taxes_paid_sum: taxes.taxes_paid(),
+            count: 1,
+        })
+    }
+
+    fn reduce(a: Self, b: Self, _context: Option<&Self::Context>) -> Self {
+        Self {
+            after_tax_wealths: merge_sorted(&a.after_tax_wealths, &b.after_tax_wealths),
+            tax_deferred_wealths: merge_sorted(&a.tax_deferred_wealths, &b.tax_deferred_wealths),
+            taxes_paid_sum: a.taxes_paid_sum + b.taxes_paid_sum,
+            count: a.count + b.count,
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use crate::{
+    number::Percent,
+    pricing::{PriceHistoryDescriptor, VariantOutcome},
+};
+
+use super::{
+    cash_flow::{merge_sorted, percentile_of},
+    PriceHistoryStatisticValue, UntrackedOutcome,
+};
+
+/// The distribution across paths of how much equity went to trading costs, and how often
+/// each path traded.
+#[derive(Debug, Clone)]
+pub struct CostDragDistribution {
+    drags: Vec<f64>,
+    trades: u64,
+    count: u64,
+}
+
+impl CostDragDistribution {
+    pub fn mean_drag(&self) -> Percent {
+        Percent::from_decimal(self.drags.iter().sum::<f64>() / self.count as f64)
+    }
+
+    pub fn drag_percentile(&self, percentile: Percent) -> Percent {
+        Percent::from_decimal(percentile_of(&self.drags, percentile))
+    }
+
+    pub fn mean_trades(&self) -> f64 {
+        self.trades as f64 / self.count as f64
+    }
+}
+
+impl PriceHistoryStatisticValue for CostDragDistribution {
+    type Context = ();
+
+    fn identity() -> Self {
+        Self {
+            drags: Vec::new(),
+            trades: 0,
+            count: 0,
+        }
+    }
+
+    fn from_outcome(
+        outcome: &VariantOutcome,
+        _descriptor: &PriceHistoryDescriptor,
+        _context: Option<&Self::Context>,
+    ) -> Result<Self, UntrackedOutcome> {
+        let cost_drag = outcome.cost_drag();
+        Ok(Self {
+            drags: vec![cost_drag.drag().as_decimal()],
+            trades: cost_drag.trades() as u64,
+            count: 1,
+        })
+    }
+
+    fn reduce(a: Self, b: Self, _context: Option<&Self::Context>) -> Self {
+        Self {
+            drags: merge_sorted(&a.drags, &b.drags),
+            trades: a.trades + b.trades,
+            count: a.count + b.count,
+        }
+    }
+}